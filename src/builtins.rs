@@ -0,0 +1,138 @@
+use crate::{errors::RuntimeError, integers::BigInt, state::Value};
+
+pub fn is_builtin(identifier: &str) -> bool {
+    arity(identifier).is_some()
+}
+
+fn arity(identifier: &str) -> Option<usize> {
+    match identifier {
+        "float" | "number" | "round" | "keys" | "values" | "len" => Some(1),
+        "get" | "has" | "remove" | "push" => Some(2),
+        "insert" => Some(3),
+        _ => None,
+    }
+}
+
+pub fn call(identifier: String, arguments: Vec<Value>) -> Result<Value, RuntimeError> {
+    let expected = arity(&identifier).unwrap_or_default();
+    if arguments.len() != expected {
+        return Err(RuntimeError::ArgumentCount {
+            identifier,
+            expected,
+            found: arguments.len(),
+        });
+    }
+    let mut arguments = arguments.into_iter();
+    let mut argument = || arguments.next().unwrap_or(Value::Empty);
+    match identifier.as_str() {
+        "keys" => {
+            let entries = expect_pantry(argument())?;
+            Ok(Value::Array(
+                entries.into_iter().map(|(key, _)| key).collect(),
+            ))
+        }
+        "values" => {
+            let entries = expect_pantry(argument())?;
+            Ok(Value::Array(
+                entries.into_iter().map(|(_, value)| value).collect(),
+            ))
+        }
+        "get" => {
+            let entries = expect_pantry(argument())?;
+            let key = argument();
+            match entries.into_iter().find(|(existing, _)| *existing == key) {
+                Some((_, value)) => Ok(value),
+                None => Err(RuntimeError::MissingKey { key }),
+            }
+        }
+        "has" => {
+            let entries = expect_pantry(argument())?;
+            let key = argument();
+            Ok(Value::Boolean(
+                entries.iter().any(|(existing, _)| *existing == key),
+            ))
+        }
+        "remove" => {
+            let mut entries = expect_pantry(argument())?;
+            let key = argument();
+            entries.retain(|(existing, _)| *existing != key);
+            Ok(Value::Pantry(entries))
+        }
+        "len" => match argument() {
+            Value::Array(array) => Ok(Value::Number(array.len() as isize)),
+            Value::String(string) => Ok(Value::Number(string.chars().count() as isize)),
+            Value::Pantry(entries) => Ok(Value::Number(entries.len() as isize)),
+            value => Err(RuntimeError::TypeMismatch {
+                expected: "array, string or pantry",
+                found: value,
+            }),
+        },
+        "push" => match argument() {
+            Value::Array(mut array) => {
+                array.push(argument());
+                Ok(Value::Array(array))
+            }
+            value => Err(RuntimeError::TypeMismatch {
+                expected: "array",
+                found: value,
+            }),
+        },
+        "insert" => {
+            let mut entries = expect_pantry(argument())?;
+            let (key, value) = (argument(), argument());
+            match entries.iter_mut().find(|(existing, _)| *existing == key) {
+                Some(entry) => entry.1 = value,
+                None => entries.push((key, value)),
+            }
+            Ok(Value::Pantry(entries))
+        }
+        _ => convert(&identifier, argument()),
+    }
+}
+
+fn expect_pantry(value: Value) -> Result<Vec<(Value, Value)>, RuntimeError> {
+    match value {
+        Value::Pantry(entries) => Ok(entries),
+        value => Err(RuntimeError::TypeMismatch {
+            expected: "pantry",
+            found: value,
+        }),
+    }
+}
+
+fn convert(identifier: &str, value: Value) -> Result<Value, RuntimeError> {
+    let value = match value {
+        Value::String(string) => parse_number(&string).unwrap_or(Value::String(string)),
+        value => value,
+    };
+    match (identifier, value) {
+        ("float", Value::Number(number)) => Ok(Value::Float(number as f64)),
+        ("float", Value::BigNumber(number)) => Ok(Value::Float(number.to_f64())),
+        ("number" | "round", value @ (Value::Number(_) | Value::BigNumber(_))) => Ok(value),
+        ("float", Value::Float(float)) => Ok(Value::Float(float)),
+        ("number", Value::Float(float)) => truncate(float),
+        ("round", Value::Float(float)) => truncate(float.round()),
+        (_, value) => Err(RuntimeError::TypeMismatch {
+            expected: "number",
+            found: value,
+        }),
+    }
+}
+
+fn truncate(float: f64) -> Result<Value, RuntimeError> {
+    BigInt::from_f64(float)
+        .map(Value::from)
+        .ok_or(RuntimeError::TypeMismatch {
+            expected: "finite number",
+            found: Value::Float(float),
+        })
+}
+
+fn parse_number(string: &str) -> Option<Value> {
+    let string = string.trim();
+    string
+        .parse()
+        .map(Value::Number)
+        .or_else(|_| string.parse().map(Value::Float))
+        .ok()
+}
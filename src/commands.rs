@@ -1,54 +1,79 @@
-use crate::{state::Value, tokens::Token};
+use crate::{expressions::Expression, patterns::Arm, spans::Spanned};
 
 #[derive(Debug, Clone)]
 pub enum Command {
     Ingredient {
         identifier: String,
-        values: Vec<Token>,
+        value: Spanned<Expression>,
     },
-    Taste {
-        identifier_1: String,
-        comparison: Token,
-        identifier_2: String,
-        tokens: Vec<Token>,
-    },
-    TasteAgain {
-        condition: bool,
-        tokens: Vec<Token>,
+    Assign {
+        identifier: String,
+        indices: Vec<Spanned<Expression>>,
+        value: Spanned<Expression>,
     },
-    Tasteless {
-        tokens: Vec<Token>,
+    Taste {
+        condition: Spanned<Expression>,
+        commands: Vec<Spanned<Command>>,
+        otherwise: Vec<Spanned<Command>>,
     },
     Layer {
-        left_value: Token,
-        right_value: Token,
-        comparison: Token,
-        commands: Vec<Command>,
+        label: Option<String>,
+        condition: Spanned<Expression>,
+        commands: Vec<Spanned<Command>>,
     },
     Simmer {
+        label: Option<String>,
         identifier: String,
-        array_identifier: String,
-        commands: Vec<Command>,
+        value_identifier: Option<String>,
+        collection: Spanned<Expression>,
+        commands: Vec<Spanned<Command>>,
     },
     Cook {
-        tokens: Vec<Token>,
-    },
-    Burnt {
+        commands: Vec<Spanned<Command>>,
         error: String,
-        tokens: Vec<Token>,
+        burnt: Vec<Spanned<Command>>,
+    },
+    Sift {
+        value: Spanned<Expression>,
+        arms: Vec<Arm>,
+    },
+    Burn {
+        value: Spanned<Expression>,
     },
     Serve {
-        identifier: String,
+        value: Spanned<Expression>,
+    },
+    Break {
+        label: Option<String>,
+    },
+    Continue {
+        label: Option<String>,
     },
     Plate {
-        value: Token,
+        value: Spanned<Expression>,
     },
     Recipe {
         identifier: String,
         params: Vec<String>,
-        commands: Vec<Command>,
+        commands: Vec<Spanned<Command>>,
+    },
+    Dish {
+        identifier: String,
+        fields: Vec<String>,
+        methods: Vec<Method>,
+    },
+    Flavour {
+        identifier: String,
+        variants: Vec<(String, Vec<String>)>,
     },
-    Maths {
-        values: Vec<Token>,
+    Expression {
+        expression: Spanned<Expression>,
     },
 }
+
+#[derive(Debug, Clone)]
+pub struct Method {
+    pub identifier: String,
+    pub params: Vec<String>,
+    pub commands: Vec<Spanned<Command>>,
+}
@@ -0,0 +1,142 @@
+use crate::{lexer::KEYWORDS, spans::Span};
+
+const RED: &str = "\x1b[1;31m";
+const BLUE: &str = "\x1b[1;34m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+}
+
+pub struct Diagnostic {
+    pub message: String,
+    pub labels: Vec<Label>,
+    pub notes: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn error(message: String) -> Self {
+        Self {
+            message,
+            labels: vec![],
+            notes: vec![],
+        }
+    }
+
+    pub fn with_label(mut self, span: Span, message: String) -> Self {
+        self.labels.push(Label { span, message });
+        self
+    }
+
+    pub fn with_help(mut self, help: String) -> Self {
+        self.notes.push(help);
+        self
+    }
+
+    pub fn render(&self, source: &str, color: bool) -> String {
+        let paint = |style: &str, text: &str| {
+            if color {
+                format!("{}{}{}", style, text, RESET)
+            } else {
+                text.to_string()
+            }
+        };
+        let lines: Vec<&str> = source.split('\n').collect();
+        let width = self
+            .labels
+            .iter()
+            .map(|label| line_of(source, label.span.end).0.to_string().len())
+            .max()
+            .unwrap_or(1);
+        let gutter = paint(BLUE, &format!("{} |", " ".repeat(width)));
+
+        let mut output = format!(
+            "{}{}\n",
+            paint(RED, "error"),
+            paint(BOLD, &format!(": {}", self.message))
+        );
+        for label in &self.labels {
+            let span = &label.span;
+            output += &format!(
+                "{}{} {}:{}:{}\n",
+                " ".repeat(width),
+                paint(BLUE, "-->"),
+                span.file,
+                span.line,
+                span.column
+            );
+            output += &format!("{}\n", gutter);
+
+            let (end_line, end_column) = line_of(source, span.end);
+            for line in span.line..=end_line {
+                let text = lines
+                    .get(line - 1)
+                    .copied()
+                    .unwrap_or("")
+                    .trim_end_matches('\r');
+                let from = if line == span.line { span.column } else { 1 };
+                let to = if line == end_line {
+                    end_column
+                } else {
+                    text.chars().count() + 1
+                };
+                let underline = "^".repeat(to.saturating_sub(from).max(1));
+                let mut marked = format!("{}{}", " ".repeat(from - 1), paint(RED, &underline));
+                if line == end_line && !label.message.is_empty() {
+                    marked += &format!(" {}", paint(RED, &label.message));
+                }
+                output += &format!(
+                    "{}\n{} {}\n",
+                    paint(BLUE, &format!("{:>width$} |", line, width = width)) + " " + text,
+                    gutter,
+                    marked
+                );
+            }
+        }
+        for note in &self.notes {
+            output += &format!(
+                "{} {} {}\n",
+                " ".repeat(width),
+                paint(BLUE, "="),
+                paint(BOLD, &format!("help: {}", note))
+            );
+        }
+        output
+    }
+}
+
+fn line_of(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset.min(source.len())];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map(|index| index + 1).unwrap_or(0);
+    (line, before[line_start..].chars().count() + 1)
+}
+
+pub fn suggest_keyword(word: &str) -> Option<&'static str> {
+    KEYWORDS
+        .iter()
+        .map(|(keyword, _)| (edit_distance(word, keyword), *keyword))
+        .filter(|(distance, keyword)| *distance <= (keyword.len() / 3).max(1))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, keyword)| keyword)
+}
+
+fn edit_distance(left: &str, right: &str) -> usize {
+    let right: Vec<char> = right.chars().collect();
+    let mut previous: Vec<usize> = (0..=right.len()).collect();
+    for (i, left_char) in left.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, right_char) in right.iter().enumerate() {
+            let cost = if left_char == *right_char { 0 } else { 1 };
+            current.push(
+                (previous[j] + cost)
+                    .min(previous[j + 1] + 1)
+                    .min(current[j] + 1),
+            );
+        }
+        previous = current;
+    }
+    previous[right.len()]
+}
@@ -0,0 +1,265 @@
+use std::fmt;
+
+use crate::{
+    diagnostics::{suggest_keyword, Diagnostic},
+    spans::Span,
+    state::Value,
+    tokens::Token,
+    utils::MathsOperations,
+};
+
+#[derive(Debug, Clone)]
+pub enum RuntimeError {
+    UndefinedVariable(String),
+    TypeMismatch {
+        expected: &'static str,
+        found: Value,
+    },
+    Incomparable {
+        left: Value,
+        right: Value,
+    },
+    DivisionByZero {
+        dividend: Value,
+        operation: MathsOperations,
+    },
+    NotIterable {
+        value: Value,
+    },
+    ZeroStep,
+    NoMatch {
+        value: Value,
+    },
+    ArgumentCount {
+        identifier: String,
+        expected: usize,
+        found: usize,
+    },
+    IndexOutOfBounds {
+        index: isize,
+        length: usize,
+    },
+    MissingKey {
+        key: Value,
+    },
+    NoField {
+        dish: String,
+        field: Value,
+    },
+    NoVariant {
+        flavour: String,
+        variant: Value,
+    },
+    TooDeep {
+        identifier: String,
+        limit: usize,
+    },
+    InvalidCommand(Token),
+    Burnt(Value),
+}
+
+impl RuntimeError {
+    pub fn to_value(&self) -> Value {
+        match self {
+            RuntimeError::Burnt(value) => value.clone(),
+            error => Value::String(error.to_string()),
+        }
+    }
+
+    pub fn diagnostic(&self, span: Span) -> Diagnostic {
+        let label = match self {
+            RuntimeError::UndefinedVariable(_) => "not found in this scope".to_string(),
+            RuntimeError::TypeMismatch { expected, .. } => format!("expected {} here", expected),
+            RuntimeError::Incomparable { .. } => "these values cannot be compared".to_string(),
+            RuntimeError::DivisionByZero { .. } => "divisor is zero".to_string(),
+            RuntimeError::NotIterable { .. } => "cannot simmer over this value".to_string(),
+            RuntimeError::ZeroStep => "this range never advances".to_string(),
+            RuntimeError::NoMatch { .. } => "no arm matches this value".to_string(),
+            RuntimeError::ArgumentCount { expected, .. } => {
+                format!("expected {} arguments", expected)
+            }
+            RuntimeError::IndexOutOfBounds { .. } => "index out of bounds".to_string(),
+            RuntimeError::MissingKey { .. } => "not in this pantry".to_string(),
+            RuntimeError::NoField { .. } => "unknown field".to_string(),
+            RuntimeError::NoVariant { .. } => "unknown variant".to_string(),
+            RuntimeError::TooDeep { .. } => "called too deeply here".to_string(),
+            RuntimeError::InvalidCommand(_) => "cannot be evaluated".to_string(),
+            RuntimeError::Burnt(_) => "burnt here".to_string(),
+        };
+        let diagnostic = Diagnostic::error(self.to_string()).with_label(span, label);
+        match self {
+            RuntimeError::Burnt(_) => {
+                diagnostic.with_help("wrap it in `cook { ... } burnt err { ... }`".to_string())
+            }
+            _ => diagnostic,
+        }
+    }
+}
+
+impl fmt::Display for RuntimeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RuntimeError::UndefinedVariable(identifier) => {
+                write!(f, "Undefined variable: {}", identifier)
+            }
+            RuntimeError::TypeMismatch { expected, found } => {
+                write!(f, "Expected {}, found ", expected)?;
+                found.fmt_nested(f)
+            }
+            RuntimeError::Incomparable { left, right } => {
+                write!(f, "Cannot compare ")?;
+                left.fmt_nested(f)?;
+                write!(f, " with ")?;
+                right.fmt_nested(f)
+            }
+            RuntimeError::DivisionByZero {
+                dividend,
+                operation,
+            } => {
+                write!(f, "Division by zero: ")?;
+                dividend.fmt_nested(f)?;
+                write!(f, " {} 0", operation)
+            }
+            RuntimeError::NotIterable { value } => {
+                value.fmt_nested(f)?;
+                write!(f, " is not iterable")
+            }
+            RuntimeError::ZeroStep => write!(f, "Range step cannot be zero"),
+            RuntimeError::NoMatch { value } => {
+                write!(f, "No arm matches ")?;
+                value.fmt_nested(f)
+            }
+            RuntimeError::ArgumentCount {
+                identifier,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Recipe {} expects {} arguments, got {}",
+                identifier, expected, found
+            ),
+            RuntimeError::IndexOutOfBounds { index, length } => {
+                write!(f, "Index {} out of bounds for length {}", index, length)
+            }
+            RuntimeError::MissingKey { key } => {
+                write!(f, "Missing key: ")?;
+                key.fmt_nested(f)
+            }
+            RuntimeError::NoField { dish, field } => {
+                write!(f, "Dish {} has no field `{}`", dish, field)
+            }
+            RuntimeError::NoVariant { flavour, variant } => {
+                write!(f, "Flavour {} has no variant `{}`", flavour, variant)
+            }
+            RuntimeError::TooDeep { identifier, limit } => write!(
+                f,
+                "Recipe {} recursed deeper than {} calls",
+                identifier, limit
+            ),
+            RuntimeError::InvalidCommand(token) => write!(f, "Invalid command: {}", token),
+            RuntimeError::Burnt(value) => write!(f, "{}", value),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ParseError {
+    pub expected: String,
+    pub found: Option<Token>,
+    pub span: Span,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.found {
+            Some(token) => write!(f, "Expected {}, found {}", self.expected, token),
+            None => write!(f, "Expected {}, found end of input", self.expected),
+        }
+    }
+}
+
+impl ParseError {
+    pub fn diagnostic(&self) -> Diagnostic {
+        let mut span = self.span.clone();
+        if let Some(Token::Block(_)) = self.found {
+            span.end = span.start + 1;
+        }
+        let diagnostic = Diagnostic::error(self.to_string())
+            .with_label(span, format!("expected {}", self.expected));
+        match &self.found {
+            Some(Token::Identifier(identifier)) => match suggest_keyword(identifier) {
+                Some(keyword) => diagnostic.with_help(format!("did you mean `{}`?", keyword)),
+                None => diagnostic,
+            },
+            _ => diagnostic,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum LexError {
+    UnexpectedCharacter { character: char, span: Span },
+    UnterminatedBlock { span: Span },
+    UnterminatedString { span: Span },
+    UnterminatedInterpolation { span: Span },
+    InvalidEscape { sequence: String, span: Span },
+    InvalidNumber { literal: String, span: Span },
+}
+
+impl LexError {
+    pub fn is_unterminated(&self) -> bool {
+        matches!(
+            self,
+            LexError::UnterminatedBlock { .. }
+                | LexError::UnterminatedString { .. }
+                | LexError::UnterminatedInterpolation { .. }
+        )
+    }
+
+    pub fn diagnostic(&self) -> Diagnostic {
+        match self {
+            LexError::UnexpectedCharacter { span, .. } => Diagnostic::error(self.to_string())
+                .with_label(span.clone(), "not valid here".to_string()),
+            LexError::UnterminatedBlock { span } => Diagnostic::error(self.to_string())
+                .with_label(span.clone(), "this block is never closed".to_string())
+                .with_help("add a matching `}`".to_string()),
+            LexError::UnterminatedString { span } => Diagnostic::error(self.to_string())
+                .with_label(span.clone(), "this string is never closed".to_string()),
+            LexError::UnterminatedInterpolation { span } => Diagnostic::error(self.to_string())
+                .with_label(
+                    span.clone(),
+                    "this interpolation is never closed".to_string(),
+                )
+                .with_help("add a matching `}` before the closing `\"`".to_string()),
+            LexError::InvalidEscape { span, .. } => Diagnostic::error(self.to_string())
+                .with_label(span.clone(), "unknown escape".to_string())
+                .with_help(
+                    "use one of `\\n`, `\\t`, `\\r`, `\\\"`, `\\\\`, `\\{`, `\\}` or `\\u{...}`"
+                        .to_string(),
+                ),
+            LexError::InvalidNumber { span, .. } => Diagnostic::error(self.to_string())
+                .with_label(span.clone(), "no digits after the prefix".to_string()),
+        }
+    }
+}
+
+impl fmt::Display for LexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LexError::UnexpectedCharacter { character, .. } => {
+                write!(f, "Unexpected character `{}`", character)
+            }
+            LexError::UnterminatedBlock { .. } => write!(f, "Unterminated block"),
+            LexError::UnterminatedString { .. } => write!(f, "Unterminated string"),
+            LexError::UnterminatedInterpolation { .. } => {
+                write!(f, "Unterminated string interpolation")
+            }
+            LexError::InvalidEscape { sequence, .. } => {
+                write!(f, "Invalid escape sequence `{}`", sequence)
+            }
+            LexError::InvalidNumber { literal, .. } => {
+                write!(f, "Invalid number literal `{}`", literal)
+            }
+        }
+    }
+}
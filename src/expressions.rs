@@ -0,0 +1,60 @@
+use crate::{integers::BigInt, spans::Spanned, tokens::Token, utils::MathsOperations};
+
+pub type Entry = (Spanned<Expression>, Spanned<Expression>);
+
+#[derive(Debug, Clone)]
+pub enum Expression {
+    Number(isize),
+    BigNumber(BigInt),
+    Float(f64),
+    String(String),
+    Interpolation(Vec<Spanned<Expression>>),
+    Boolean(bool),
+    Array(Vec<Spanned<Expression>>),
+    Pantry(Vec<Entry>),
+    Identifier(String),
+    Call {
+        callee: Box<Spanned<Expression>>,
+        arguments: Vec<Spanned<Expression>>,
+    },
+    MethodCall {
+        target: Box<Spanned<Expression>>,
+        method: String,
+        arguments: Vec<Spanned<Expression>>,
+    },
+    Index {
+        target: Box<Spanned<Expression>>,
+        index: Box<Spanned<Expression>>,
+    },
+    Slice {
+        target: Box<Spanned<Expression>>,
+        start: Option<Box<Spanned<Expression>>>,
+        end: Option<Box<Spanned<Expression>>>,
+    },
+    Range {
+        start: Box<Spanned<Expression>>,
+        end: Box<Spanned<Expression>>,
+        step: Option<Box<Spanned<Expression>>>,
+        inclusive: bool,
+    },
+    Negate(Box<Spanned<Expression>>),
+    Not(Box<Spanned<Expression>>),
+    Binary {
+        left: Box<Spanned<Expression>>,
+        operation: MathsOperations,
+        right: Box<Spanned<Expression>>,
+    },
+    Comparison {
+        left: Box<Spanned<Expression>>,
+        comparison: Token,
+        right: Box<Spanned<Expression>>,
+    },
+    Mix {
+        left: Box<Spanned<Expression>>,
+        right: Box<Spanned<Expression>>,
+    },
+    Separate {
+        left: Box<Spanned<Expression>>,
+        right: Box<Spanned<Expression>>,
+    },
+}
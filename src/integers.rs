@@ -0,0 +1,355 @@
+use std::{
+    cmp::Ordering,
+    fmt,
+    ops::{Add, Mul, Neg, Sub},
+};
+
+const BASE: u64 = 1_000_000_000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BigInt {
+    negative: bool,
+    limbs: Vec<u32>,
+}
+
+impl BigInt {
+    pub fn parse(digits: &str, radix: u32) -> Option<Self> {
+        let mut limbs = vec![];
+        for ch in digits.chars() {
+            let digit = ch.to_digit(radix)?;
+            limbs = add_small(&mul_small(&limbs, radix), digit);
+        }
+        Some(Self::new(false, limbs))
+    }
+
+    pub fn from_f64(float: f64) -> Option<Self> {
+        if !float.is_finite() {
+            return None;
+        }
+        let magnitude = Self::parse(&format!("{:.0}", float.trunc().abs()), 10)?;
+        Some(if float < 0.0 { -magnitude } else { magnitude })
+    }
+
+    pub fn to_isize(&self) -> Option<isize> {
+        let mut magnitude: i128 = 0;
+        for limb in self.limbs.iter().rev() {
+            magnitude = magnitude
+                .checked_mul(BASE as i128)?
+                .checked_add(*limb as i128)?;
+        }
+        let number = if self.negative { -magnitude } else { magnitude };
+        isize::try_from(number).ok()
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        let magnitude = self
+            .limbs
+            .iter()
+            .rev()
+            .fold(0.0, |acc, limb| acc * BASE as f64 + *limb as f64);
+        if self.negative {
+            -magnitude
+        } else {
+            magnitude
+        }
+    }
+
+    pub fn div_rem(&self, other: &BigInt) -> Option<(BigInt, BigInt)> {
+        if other.limbs.is_empty() {
+            return None;
+        }
+        let (quotient, remainder) = div_rem_magnitude(&self.limbs, &other.limbs);
+        Some((
+            Self::new(self.negative != other.negative, quotient),
+            Self::new(self.negative, remainder),
+        ))
+    }
+
+    fn new(negative: bool, limbs: Vec<u32>) -> Self {
+        let limbs = trim(limbs);
+        Self {
+            negative: negative && !limbs.is_empty(),
+            limbs,
+        }
+    }
+}
+
+impl From<isize> for BigInt {
+    fn from(number: isize) -> Self {
+        let mut magnitude = number.unsigned_abs() as u64;
+        let mut limbs = vec![];
+        while magnitude > 0 {
+            limbs.push((magnitude % BASE) as u32);
+            magnitude /= BASE;
+        }
+        Self::new(number < 0, limbs)
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => cmp_magnitude(&self.limbs, &other.limbs),
+            (true, true) => cmp_magnitude(&other.limbs, &self.limbs),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Neg for BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        Self::new(!self.negative, self.limbs)
+    }
+}
+
+impl Add for &BigInt {
+    type Output = BigInt;
+
+    fn add(self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::new(self.negative, add_magnitude(&self.limbs, &other.limbs));
+        }
+        match cmp_magnitude(&self.limbs, &other.limbs) {
+            Ordering::Less => BigInt::new(other.negative, sub_magnitude(&other.limbs, &self.limbs)),
+            _ => BigInt::new(self.negative, sub_magnitude(&self.limbs, &other.limbs)),
+        }
+    }
+}
+
+impl Sub for &BigInt {
+    type Output = BigInt;
+
+    fn sub(self, other: &BigInt) -> BigInt {
+        self + &-other.clone()
+    }
+}
+
+impl Mul for &BigInt {
+    type Output = BigInt;
+
+    fn mul(self, other: &BigInt) -> BigInt {
+        BigInt::new(
+            self.negative != other.negative,
+            mul_magnitude(&self.limbs, &other.limbs),
+        )
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let Some((last, rest)) = self.limbs.split_last() else {
+            return write!(f, "0");
+        };
+        if self.negative {
+            write!(f, "-")?;
+        }
+        write!(f, "{}", last)?;
+        for limb in rest.iter().rev() {
+            write!(f, "{:09}", limb)?;
+        }
+        Ok(())
+    }
+}
+
+fn cmp_magnitude(left: &[u32], right: &[u32]) -> Ordering {
+    left.len()
+        .cmp(&right.len())
+        .then_with(|| left.iter().rev().cmp(right.iter().rev()))
+}
+
+fn add_magnitude(left: &[u32], right: &[u32]) -> Vec<u32> {
+    let mut limbs = vec![];
+    let mut carry = 0;
+    for i in 0..left.len().max(right.len()) {
+        let sum = *left.get(i).unwrap_or(&0) as u64 + *right.get(i).unwrap_or(&0) as u64 + carry;
+        limbs.push((sum % BASE) as u32);
+        carry = sum / BASE;
+    }
+    if carry > 0 {
+        limbs.push(carry as u32);
+    }
+    limbs
+}
+
+fn sub_magnitude(left: &[u32], right: &[u32]) -> Vec<u32> {
+    let mut limbs = vec![];
+    let mut borrow = 0;
+    for (i, limb) in left.iter().enumerate() {
+        let subtrahend = *right.get(i).unwrap_or(&0) as i64 + borrow;
+        let mut difference = *limb as i64 - subtrahend;
+        borrow = 0;
+        if difference < 0 {
+            difference += BASE as i64;
+            borrow = 1;
+        }
+        limbs.push(difference as u32);
+    }
+    trim(limbs)
+}
+
+fn mul_magnitude(left: &[u32], right: &[u32]) -> Vec<u32> {
+    let mut limbs = vec![0u64; left.len() + right.len()];
+    for (i, left_limb) in left.iter().enumerate() {
+        let mut carry = 0;
+        for (j, right_limb) in right.iter().enumerate() {
+            let product = limbs[i + j] + *left_limb as u64 * *right_limb as u64 + carry;
+            limbs[i + j] = product % BASE;
+            carry = product / BASE;
+        }
+        limbs[i + right.len()] += carry;
+    }
+    trim(limbs.into_iter().map(|limb| limb as u32).collect())
+}
+
+fn mul_small(limbs: &[u32], factor: u32) -> Vec<u32> {
+    mul_magnitude(limbs, &[factor])
+}
+
+fn add_small(limbs: &[u32], addend: u32) -> Vec<u32> {
+    add_magnitude(limbs, &[addend])
+}
+
+fn div_rem_magnitude(dividend: &[u32], divisor: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    let mut quotient = vec![];
+    let mut remainder: Vec<u32> = vec![];
+    for limb in dividend.iter().rev() {
+        remainder.insert(0, *limb);
+        remainder = trim(remainder);
+        let (mut low, mut high) = (0, BASE as u32 - 1);
+        while low < high {
+            let middle = low + (high - low).div_ceil(2);
+            if cmp_magnitude(&mul_small(divisor, middle), &remainder) == Ordering::Greater {
+                high = middle - 1;
+            } else {
+                low = middle;
+            }
+        }
+        remainder = sub_magnitude(&remainder, &mul_small(divisor, low));
+        quotient.push(low);
+    }
+    quotient.reverse();
+    (trim(quotient), remainder)
+}
+
+fn trim(mut limbs: Vec<u32>) -> Vec<u32> {
+    while limbs.last() == Some(&0) {
+        limbs.pop();
+    }
+    limbs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(digits: &str) -> BigInt {
+        match digits.strip_prefix('-') {
+            Some(digits) => -BigInt::parse(digits, 10).unwrap(),
+            None => BigInt::parse(digits, 10).unwrap(),
+        }
+    }
+
+    #[test]
+    fn parses_and_displays() {
+        assert_eq!(big("0").to_string(), "0");
+        assert_eq!(big("-0").to_string(), "0");
+        assert_eq!(big("1000000000").to_string(), "1000000000");
+        assert_eq!(
+            big("-123456789012345678901").to_string(),
+            "-123456789012345678901"
+        );
+        assert_eq!(BigInt::parse("ff", 16).unwrap().to_string(), "255");
+        assert_eq!(BigInt::parse("12", 2), None);
+    }
+
+    #[test]
+    fn handles_signs() {
+        assert_eq!(&big("5") + &big("-8"), big("-3"));
+        assert_eq!(&big("-5") + &big("8"), big("3"));
+        assert_eq!(&big("-5") - &big("-5"), big("0"));
+        assert_eq!(&big("-4") * &big("3"), big("-12"));
+        assert_eq!(&big("-4") * &big("-3"), big("12"));
+        assert_eq!(&big("-4") * &big("0"), big("0"));
+        assert!(big("-10") < big("-9"));
+        assert!(big("-1") < big("0"));
+    }
+
+    #[test]
+    fn carries_and_borrows_across_limbs() {
+        assert_eq!(&big("999999999") + &big("1"), big("1000000000"));
+        assert_eq!(
+            &big("999999999999999999") + &big("1"),
+            big("1000000000000000000")
+        );
+        assert_eq!(&big("1000000000") - &big("1"), big("999999999"));
+        assert_eq!(
+            &big("1000000000000000000") - &big("1"),
+            big("999999999999999999")
+        );
+        assert_eq!(
+            &big("999999999") * &big("999999999"),
+            big("999999998000000001")
+        );
+    }
+
+    #[test]
+    fn divides_towards_zero() {
+        let cases = [
+            ("7", "2", "3", "1"),
+            ("-7", "2", "-3", "-1"),
+            ("7", "-2", "-3", "1"),
+            ("-7", "-2", "3", "-1"),
+            (
+                "1000000000000000000000",
+                "1000000007",
+                "999999993000",
+                "49000",
+            ),
+        ];
+        for (dividend, divisor, quotient, remainder) in cases {
+            assert_eq!(
+                big(dividend).div_rem(&big(divisor)),
+                Some((big(quotient), big(remainder)))
+            );
+        }
+        assert_eq!(big("1").div_rem(&big("0")), None);
+    }
+
+    #[test]
+    fn converts_floats() {
+        assert_eq!(BigInt::from_f64(-2.9), Some(big("-2")));
+        assert_eq!(BigInt::from_f64(-0.5), Some(big("0")));
+        assert_eq!(
+            BigInt::from_f64(1e30),
+            Some(big("1000000000000000019884624838656"))
+        );
+        assert_eq!(BigInt::from_f64(f64::NAN), None);
+        assert_eq!(BigInt::from_f64(f64::NEG_INFINITY), None);
+    }
+
+    #[test]
+    fn converts_at_the_boundaries() {
+        assert_eq!(BigInt::from(isize::MAX).to_isize(), Some(isize::MAX));
+        assert_eq!(BigInt::from(isize::MIN).to_isize(), Some(isize::MIN));
+        assert_eq!((&BigInt::from(isize::MAX) + &big("1")).to_isize(), None);
+        assert_eq!((&BigInt::from(isize::MIN) - &big("1")).to_isize(), None);
+        assert_eq!(big(&i128::MAX.to_string()).to_isize(), None);
+        assert_eq!(
+            big("170141183460469231731687303715884105728").to_isize(),
+            None
+        );
+        assert_eq!(
+            big("-170141183460469231731687303715884105729").to_isize(),
+            None
+        );
+    }
+}
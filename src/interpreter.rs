@@ -1,231 +1,1247 @@
-use core::num;
+use std::{cmp::Ordering, collections::HashMap, mem, rc::Rc};
 
 use crate::{
-    commands::Command,
-    state::{State, Value},
+    builtins,
+    commands::{Command, Method},
+    errors::RuntimeError,
+    expressions::Expression,
+    integers::BigInt,
+    patterns::{Arm, Pattern},
+    spans::{Span, Spanned},
+    state::{self, Dish, Flavour, Range, Recipe, State, Value},
     tokens::Token,
     utils::MathsOperations,
 };
 
+const MAX_DEPTH: usize = 1000;
+
+pub enum Signal {
+    Serve(Value),
+    Break(Option<String>),
+    Continue(Option<String>),
+}
+
 pub struct Interpreter {
     state: State,
+    error_span: Option<Span>,
+    depth: usize,
 }
 
 impl Interpreter {
     pub fn new() -> Self {
         Self {
             state: State::new(),
+            error_span: None,
+            depth: 0,
         }
     }
 
-    pub fn execute(&mut self, commands: Vec<Command>) {
+    pub fn error_span(&self) -> Option<&Span> {
+        self.error_span.as_ref()
+    }
+
+    pub fn execute(
+        &mut self,
+        commands: &[Spanned<Command>],
+    ) -> Result<Option<Signal>, RuntimeError> {
         for command in commands {
-            match command {
-                Command::Ingredient { identifier, values } => {
-                    self.execute_ingredient(identifier, values)
-                }
-                Command::Plate { value } => self.execute_plate(value),
-                Command::Layer {
-                    left_value,
-                    right_value,
-                    comparison,
-                    commands,
-                } => self.execute_layer(left_value, right_value, comparison, commands),
-                Command::Simmer {
-                    identifier,
-                    array_identifier,
-                    commands,
-                } => self.execute_simmer(identifier, array_identifier, commands),
-                Command::Recipe {
-                    identifier,
-                    params,
-                    commands,
-                } => self.execute_recipe(identifier, params, commands),
-                Command::Maths { values } => self.execute_maths(values),
-                _ => panic!("Invalid command"),
+            let served = match self.execute_command(&command.node) {
+                Ok(served) => served,
+                Err(error) => {
+                    self.error_span.get_or_insert(command.span.clone());
+                    return Err(error);
+                }
             };
+            if served.is_some() {
+                return Ok(served);
+            }
         }
+        Ok(None)
     }
 
-    fn execute_maths(&mut self, values: Vec<Token>) -> Option<Value> {
-        let mut result = 0;
-        let mut operation = &MathsOperations::Plus;
-        for val in values.iter() {
-            let number = match val {
-                Token::Number(num) => Some(*num),
-                Token::Identifier(identifier) => {
-                    let value = self.state.get_variable(identifier.clone())?;
-                    match value {
-                        Value::Number(num) => Some(num),
-                        _ => panic!("Operand should be number"),
-                    }
+    fn execute_command(&mut self, command: &Command) -> Result<Option<Signal>, RuntimeError> {
+        match command {
+            Command::Ingredient { identifier, value } => self.execute_ingredient(identifier, value),
+            Command::Assign {
+                identifier,
+                indices,
+                value,
+            } => self.execute_assign(identifier, indices, value),
+            Command::Plate { value } => self.execute_plate(value),
+            Command::Layer {
+                label,
+                condition,
+                commands,
+            } => self.execute_layer(label, condition, commands),
+            Command::Taste {
+                condition,
+                commands,
+                otherwise,
+            } => self.execute_taste(condition, commands, otherwise),
+            Command::Simmer {
+                label,
+                identifier,
+                value_identifier,
+                collection,
+                commands,
+            } => self.execute_simmer(label, identifier, value_identifier, collection, commands),
+            Command::Cook {
+                commands,
+                error,
+                burnt,
+            } => self.execute_cook(commands, error, burnt),
+            Command::Sift { value, arms } => self.execute_sift(value, arms),
+            Command::Burn { value } => self.execute_burn(value),
+            Command::Recipe {
+                identifier,
+                params,
+                commands,
+            } => self.execute_recipe(identifier, params, commands),
+            Command::Dish {
+                identifier,
+                fields,
+                methods,
+            } => self.execute_dish(identifier, fields, methods),
+            Command::Flavour {
+                identifier,
+                variants,
+            } => self.execute_flavour(identifier, variants),
+            Command::Serve { value } => self.execute_serve(value),
+            Command::Break { label } => Ok(Some(Signal::Break(label.clone()))),
+            Command::Continue { label } => Ok(Some(Signal::Continue(label.clone()))),
+            Command::Expression { expression } => {
+                self.execute_expression(expression)?;
+                Ok(None)
+            }
+        }
+    }
+
+    fn execute_expression(
+        &mut self,
+        expression: &Spanned<Expression>,
+    ) -> Result<Value, RuntimeError> {
+        let value = match &expression.node {
+            Expression::Number(number) => Ok(Value::Number(*number)),
+            Expression::BigNumber(number) => Ok(Value::BigNumber(number.clone())),
+            Expression::Float(float) => Ok(Value::Float(*float)),
+            Expression::String(string) => Ok(Value::String(string.clone())),
+            Expression::Interpolation(parts) => {
+                let mut string = String::new();
+                for part in parts {
+                    string += &self.execute_expression(part)?.to_string();
                 }
-                _ => None,
-            };
-            if let Some(num) = number {
-                result = match operation {
-                    MathsOperations::Plus => result + num,
-                    MathsOperations::Minus => result - num,
-                    MathsOperations::Multiply => result * num,
-                    MathsOperations::Division => result / num,
-                    MathsOperations::Mod => result % num,
+                Ok(Value::String(string))
+            }
+            Expression::Boolean(boolean) => Ok(Value::Boolean(*boolean)),
+            Expression::Array(elements) => elements
+                .iter()
+                .map(|element| self.execute_expression(element))
+                .collect::<Result<Vec<_>, _>>()
+                .map(Value::Array),
+            Expression::Pantry(entries) => {
+                let mut pantry = vec![];
+                for (key, value) in entries {
+                    let key = self.execute_expression(key)?;
+                    let value = self.execute_expression(value)?;
+                    pantry.retain(|(existing, _)| *existing != key);
+                    pantry.push((key, value));
+                }
+                Ok(Value::Pantry(pantry))
+            }
+            Expression::Identifier(identifier) => self.get_variable(identifier.clone()),
+            Expression::Call { callee, arguments } => self.execute_call(callee, arguments),
+            Expression::MethodCall {
+                target,
+                method,
+                arguments,
+            } => self.execute_method_call(target, method.clone(), arguments),
+            Expression::Index { target, index } => {
+                let target = self.execute_expression(target)?;
+                let index = self.execute_expression(index)?;
+                self.execute_index(target, index)
+            }
+            Expression::Slice { target, start, end } => {
+                let target = self.execute_expression(target)?;
+                let start = match start {
+                    Some(start) => Some(self.execute_expression(start)?),
+                    None => None,
+                };
+                let end = match end {
+                    Some(end) => Some(self.execute_expression(end)?),
+                    None => None,
+                };
+                self.execute_slice(target, start, end)
+            }
+            Expression::Range {
+                start,
+                end,
+                step,
+                inclusive,
+            } => {
+                let start = self.execute_number(start)?;
+                let end = self.execute_number(end)?;
+                let step = match step {
+                    Some(step) => self.execute_number(step)?,
+                    None if start <= end => 1,
+                    None => -1,
+                };
+                if step == 0 {
+                    Err(RuntimeError::ZeroStep)
+                } else {
+                    Ok(Value::Range(Range {
+                        start,
+                        end,
+                        step,
+                        inclusive: *inclusive,
+                    }))
                 }
-            } else {
-                operation = match val {
-                    Token::Maths(oper) => oper,
-                    _ => panic!("Invalid operation"),
+            }
+            Expression::Negate(operand) => match self.execute_expression(operand)? {
+                Value::Number(number) => Ok(number
+                    .checked_neg()
+                    .map(Value::Number)
+                    .unwrap_or_else(|| Value::from(-BigInt::from(number)))),
+                Value::BigNumber(number) => Ok(Value::from(-number)),
+                Value::Float(float) => Ok(Value::Float(-float)),
+                value => Err(RuntimeError::TypeMismatch {
+                    expected: "number",
+                    found: value,
+                }),
+            },
+            Expression::Not(operand) => match self.execute_expression(operand)? {
+                Value::Boolean(boolean) => Ok(Value::Boolean(!boolean)),
+                value => Err(RuntimeError::TypeMismatch {
+                    expected: "boolean",
+                    found: value,
+                }),
+            },
+            Expression::Comparison {
+                left,
+                comparison,
+                right,
+            } => {
+                let left = self.execute_expression(left)?;
+                let right = self.execute_expression(right)?;
+                self.execute_comparison(left, comparison, right)
+                    .map(Value::Boolean)
+            }
+            Expression::Mix { left, right } => {
+                if self.execute_condition(left)? {
+                    self.execute_condition(right).map(Value::Boolean)
+                } else {
+                    Ok(Value::Boolean(false))
                 }
             }
+            Expression::Separate { left, right } => {
+                if self.execute_condition(left)? {
+                    Ok(Value::Boolean(true))
+                } else {
+                    self.execute_condition(right).map(Value::Boolean)
+                }
+            }
+            Expression::Binary {
+                left,
+                operation,
+                right,
+            } => {
+                let left = self.execute_expression(left)?;
+                let right = self.execute_expression(right)?;
+                self.execute_maths(left, operation, right)
+            }
+        };
+        if value.is_err() {
+            self.error_span.get_or_insert(expression.span.clone());
         }
-        Some(Value::Number(result))
+        value
     }
 
-    fn execute_ingredient(&mut self, identifier: String, values: Vec<Token>) -> Option<Value> {
-        if values.len() > 1 {
-            let result = self.execute_maths(values)?;
-            return self.state.add_variable(&identifier, result);
+    fn execute_number(&mut self, expression: &Spanned<Expression>) -> Result<isize, RuntimeError> {
+        match self.execute_expression(expression)? {
+            Value::Number(number) => Ok(number),
+            value => {
+                self.error_span.get_or_insert(expression.span.clone());
+                Err(RuntimeError::TypeMismatch {
+                    expected: "number",
+                    found: value,
+                })
+            }
         }
-        let value = values[0].clone();
+    }
+
+    fn get_variable(&mut self, identifier: String) -> Result<Value, RuntimeError> {
+        self.state
+            .get_variable(identifier.clone())
+            .ok_or(RuntimeError::UndefinedVariable(identifier))
+    }
+
+    fn execute_call(
+        &mut self,
+        callee: &Spanned<Expression>,
+        arguments: &[Spanned<Expression>],
+    ) -> Result<Value, RuntimeError> {
+        let (identifier, value) = match &callee.node {
+            Expression::Identifier(identifier) => match self.state.get_variable(identifier.clone())
+            {
+                Some(value) => (identifier.clone(), value),
+                None if builtins::is_builtin(identifier) => {
+                    let values = arguments
+                        .iter()
+                        .map(|argument| self.execute_expression(argument))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    return builtins::call(identifier.clone(), values);
+                }
+                None => return Err(RuntimeError::UndefinedVariable(identifier.clone())),
+            },
+            _ => {
+                let value = self.execute_expression(callee)?;
+                (value.to_string(), value)
+            }
+        };
         match value {
-            Token::ArrayNumber(array) => self
-                .state
-                .add_variable(&identifier, Value::ArrayNumber(array)),
-            Token::ArrayString(array) => self
-                .state
-                .add_variable(&identifier, Value::ArrayString(array)),
-            Token::Number(number) => self.state.add_variable(&identifier, Value::Number(number)),
-            Token::StringLiteral(string) => {
-                self.state.add_variable(&identifier, Value::String(string))
+            Value::Function(recipe) => {
+                self.execute_recipe_call(identifier, recipe, arguments, None)
+            }
+            Value::Dish(dish) => self.execute_instance(identifier, dish, arguments),
+            value => {
+                self.error_span.get_or_insert(callee.span.clone());
+                Err(RuntimeError::TypeMismatch {
+                    expected: "recipe",
+                    found: value,
+                })
             }
-            _ => panic!("Invalid value for identifier: {}", identifier),
         }
     }
 
-    fn execute_layer(
+    fn execute_method_call(
         &mut self,
-        left_value: Token,
-        right_value: Token,
-        comparison: Token,
-        commands: Vec<Command>,
-    ) -> Option<Value> {
-        let error_message = "Operator should be number for layer";
-        loop {
-            let operator_1 = match &left_value {
-                &Token::Number(number) => number,
-                Token::Identifier(identifier) => {
-                    let value = self.state.get_variable(identifier.to_string())?;
-                    if let Value::Number(number) = value {
-                        number
-                    } else {
-                        panic!("{}", error_message);
-                    }
+        target: &Spanned<Expression>,
+        method: String,
+        arguments: &[Spanned<Expression>],
+    ) -> Result<Value, RuntimeError> {
+        let place = match place(target) {
+            Some((identifier, indices)) => {
+                let indices = indices
+                    .into_iter()
+                    .map(|index| self.execute_expression(index))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Some((identifier, indices))
+            }
+            None => None,
+        };
+        let target = match &place {
+            Some((identifier, indices)) => {
+                let mut target = self.get_variable(identifier.to_string())?;
+                for index in indices {
+                    target = self.execute_index(target, index.clone())?;
                 }
-                _ => panic!("{}", error_message),
-            };
-            let operator_2 = match &right_value {
-                &Token::Number(number) => number,
-                Token::Identifier(identifier) => {
-                    let value = self.state.get_variable(identifier.to_string())?;
-                    if let Value::Number(number) = value {
-                        number
-                    } else {
-                        panic!("{}", error_message)
-                    }
+                target
+            }
+            None => self.execute_expression(target)?,
+        };
+        if let Value::Flavour(flavour) = &target {
+            let position = resolve_variant(flavour, &Value::String(method.clone()))?;
+            let expected = flavour.variants[position].1.len();
+            if expected != arguments.len() {
+                return Err(RuntimeError::ArgumentCount {
+                    identifier: method,
+                    expected,
+                    found: arguments.len(),
+                });
+            }
+            let values = arguments
+                .iter()
+                .map(|argument| self.execute_expression(argument))
+                .collect::<Result<Vec<_>, _>>()?;
+            return Ok(Value::Variant(flavour.clone(), position, values));
+        }
+        if let Value::Instance(dish, _) = &target {
+            if let Some((_, recipe)) = dish.methods.iter().find(|(name, _)| *name == method) {
+                let recipe = recipe.clone();
+                let mut receiver = target;
+                let served =
+                    self.execute_recipe_call(method, recipe, arguments, Some(&mut receiver))?;
+                if let Some((identifier, indices)) = place {
+                    let root = self.get_variable(identifier.to_string())?;
+                    let root = self.execute_index_assign(root, &indices, receiver)?;
+                    self.state.set_variable(identifier, root);
                 }
-                _ => panic!("{}", error_message),
-            };
-            let comparison = match comparison {
-                Token::Hotter => operator_1 > operator_2,
-                Token::Cooler => operator_1 < operator_2,
-                Token::Same => operator_1 == operator_2,
-                Token::HotOrSame => operator_1 >= operator_2,
-                Token::CoolOrSame => operator_1 <= operator_2,
-                Token::NotSame => operator_1 != operator_2,
-                _ => panic!("Invalid comparison operator"),
-            };
-            if comparison {
-                self.execute(commands.clone());
-            } else {
-                break;
+                return Ok(served);
             }
         }
-        None
+        match self.execute_index(target, Value::String(method.clone()))? {
+            Value::Function(recipe) => self.execute_recipe_call(method, recipe, arguments, None),
+            Value::Dish(dish) => self.execute_instance(method, dish, arguments),
+            value => Err(RuntimeError::TypeMismatch {
+                expected: "recipe",
+                found: value,
+            }),
+        }
     }
 
-    fn execute_simmer(
+    fn execute_instance(
         &mut self,
         identifier: String,
-        array_identifier: String,
-        commands: Vec<Command>,
-    ) -> Option<Value> {
-        let value = self.state.get_variable(array_identifier)?;
-        match value {
-            Value::ArrayNumber(array) => {
-                for val in array {
-                    self.state.add_variable(&identifier, Value::Number(val));
-                    self.execute(commands.clone());
+        dish: Rc<Dish>,
+        arguments: &[Spanned<Expression>],
+    ) -> Result<Value, RuntimeError> {
+        if dish.fields.len() != arguments.len() {
+            return Err(RuntimeError::ArgumentCount {
+                identifier,
+                expected: dish.fields.len(),
+                found: arguments.len(),
+            });
+        }
+        let values = arguments
+            .iter()
+            .map(|argument| self.execute_expression(argument))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Value::Instance(dish, values))
+    }
+
+    fn execute_recipe_call(
+        &mut self,
+        identifier: String,
+        recipe: Rc<Recipe>,
+        arguments: &[Spanned<Expression>],
+        receiver: Option<&mut Value>,
+    ) -> Result<Value, RuntimeError> {
+        let mut frame = HashMap::new();
+        if let Some(receiver) = &receiver {
+            frame.insert("self".to_string(), (*receiver).clone());
+        }
+        if recipe.params.len() != arguments.len() {
+            return Err(RuntimeError::ArgumentCount {
+                identifier,
+                expected: recipe.params.len(),
+                found: arguments.len(),
+            });
+        }
+        for (param, argument) in recipe.params.iter().zip(arguments) {
+            let value = self.execute_expression(argument)?;
+            frame.insert(param.clone(), value);
+        }
+        if self.depth == MAX_DEPTH {
+            return Err(RuntimeError::TooDeep {
+                identifier,
+                limit: MAX_DEPTH,
+            });
+        }
+        let scopes = self.state.push_frame(recipe.environment.clone(), frame);
+        self.depth += 1;
+        let served = self.execute(&recipe.commands);
+        self.depth -= 1;
+        let frame = self.state.pop_frame(scopes);
+        if let (Some(receiver), Some(frame)) = (receiver, &frame) {
+            if let Some(value) = frame.borrow().get("self").cloned() {
+                *receiver = value;
+            }
+        }
+        if let Some(frame) = frame {
+            state::release(frame);
+        }
+        match served? {
+            Some(Signal::Serve(value)) => Ok(value),
+            _ => Ok(Value::Empty),
+        }
+    }
+
+    fn execute_serve(
+        &mut self,
+        value: &Spanned<Expression>,
+    ) -> Result<Option<Signal>, RuntimeError> {
+        Ok(Some(Signal::Serve(self.execute_expression(value)?)))
+    }
+
+    fn execute_maths(
+        &mut self,
+        left: Value,
+        operation: &MathsOperations,
+        right: Value,
+    ) -> Result<Value, RuntimeError> {
+        if let (MathsOperations::Plus, Value::String(_), _)
+        | (MathsOperations::Plus, _, Value::String(_)) = (operation, &left, &right)
+        {
+            return Ok(Value::String(format!("{}{}", left, right)));
+        }
+        if let (MathsOperations::Plus, Value::Array(left), Value::Array(right)) =
+            (operation, &left, &right)
+        {
+            return Ok(Value::Array([left.as_slice(), right.as_slice()].concat()));
+        }
+        if let (Value::Number(left), Value::Number(right)) = (&left, &right) {
+            return self.execute_number_maths(*left, operation, *right);
+        }
+        if let (Some(left), Some(right)) = (left.to_integer(), right.to_integer()) {
+            return self.execute_big_maths(left, operation, right);
+        }
+        match (left.to_float(), right.to_float()) {
+            (Some(left), Some(right)) => self.execute_float_maths(left, operation, right),
+            (Some(_), _) => Err(RuntimeError::TypeMismatch {
+                expected: "number",
+                found: right,
+            }),
+            _ => Err(RuntimeError::TypeMismatch {
+                expected: "number",
+                found: left,
+            }),
+        }
+    }
+
+    fn execute_number_maths(
+        &mut self,
+        left: isize,
+        operation: &MathsOperations,
+        right: isize,
+    ) -> Result<Value, RuntimeError> {
+        let result = match operation {
+            MathsOperations::Plus => left.checked_add(right),
+            MathsOperations::Minus => left.checked_sub(right),
+            MathsOperations::Multiply => left.checked_mul(right),
+            MathsOperations::Division | MathsOperations::Mod if right == 0 => {
+                return Err(RuntimeError::DivisionByZero {
+                    dividend: Value::Number(left),
+                    operation: operation.clone(),
+                })
+            }
+            MathsOperations::Division => left.checked_div(right),
+            MathsOperations::Mod => left.checked_rem(right),
+        };
+        match result {
+            Some(result) => Ok(Value::Number(result)),
+            None => self.execute_big_maths(BigInt::from(left), operation, BigInt::from(right)),
+        }
+    }
+
+    fn execute_big_maths(
+        &mut self,
+        left: BigInt,
+        operation: &MathsOperations,
+        right: BigInt,
+    ) -> Result<Value, RuntimeError> {
+        let result = match operation {
+            MathsOperations::Plus => &left + &right,
+            MathsOperations::Minus => &left - &right,
+            MathsOperations::Multiply => &left * &right,
+            MathsOperations::Division | MathsOperations::Mod => match left.div_rem(&right) {
+                Some((quotient, _)) if *operation == MathsOperations::Division => quotient,
+                Some((_, remainder)) => remainder,
+                None => {
+                    return Err(RuntimeError::DivisionByZero {
+                        dividend: Value::from(left),
+                        operation: operation.clone(),
+                    })
                 }
+            },
+        };
+        Ok(Value::from(result))
+    }
+
+    fn execute_float_maths(
+        &mut self,
+        left: f64,
+        operation: &MathsOperations,
+        right: f64,
+    ) -> Result<Value, RuntimeError> {
+        let result = match operation {
+            MathsOperations::Plus => left + right,
+            MathsOperations::Minus => left - right,
+            MathsOperations::Multiply => left * right,
+            MathsOperations::Division | MathsOperations::Mod if right == 0.0 => {
+                return Err(RuntimeError::DivisionByZero {
+                    dividend: Value::Float(left),
+                    operation: operation.clone(),
+                })
+            }
+            MathsOperations::Division => left / right,
+            MathsOperations::Mod => left % right,
+        };
+        Ok(Value::Float(result))
+    }
+
+    fn execute_ingredient(
+        &mut self,
+        identifier: &String,
+        value: &Spanned<Expression>,
+    ) -> Result<Option<Signal>, RuntimeError> {
+        let value = self.execute_expression(value)?;
+        self.state.add_variable(identifier, value);
+        Ok(None)
+    }
+
+    fn execute_assign(
+        &mut self,
+        identifier: &String,
+        indices: &[Spanned<Expression>],
+        value: &Spanned<Expression>,
+    ) -> Result<Option<Signal>, RuntimeError> {
+        let mut value = self.execute_expression(value)?;
+        if !indices.is_empty() {
+            let indices = indices
+                .iter()
+                .map(|index| self.execute_expression(index))
+                .collect::<Result<Vec<_>, _>>()?;
+            let target = self.get_variable(identifier.clone())?;
+            value = self.execute_index_assign(target, &indices, value)?;
+        }
+        match self.state.set_variable(identifier, value) {
+            Some(_) => Ok(None),
+            None => Err(RuntimeError::UndefinedVariable(identifier.clone())),
+        }
+    }
+
+    fn execute_index(&self, target: Value, index: Value) -> Result<Value, RuntimeError> {
+        match target {
+            Value::Array(mut array) => {
+                let position = resolve_index(&index, array.len(), false)?;
+                Ok(array.swap_remove(position))
+            }
+            Value::String(string) => {
+                let chars: Vec<char> = string.chars().collect();
+                let position = resolve_index(&index, chars.len(), false)?;
+                Ok(Value::String(chars[position].to_string()))
             }
-            Value::ArrayString(array) => {
-                for val in array {
-                    self.state.add_variable(&identifier, Value::String(val));
-                    self.execute(commands.clone());
+            Value::Pantry(entries) => entries
+                .into_iter()
+                .find(|(key, _)| *key == index)
+                .map(|(_, value)| value)
+                .ok_or(RuntimeError::MissingKey { key: index }),
+            Value::Instance(dish, mut values) => {
+                let position = resolve_field(&dish, &index)?;
+                Ok(values.swap_remove(position))
+            }
+            Value::Flavour(flavour) => {
+                let position = resolve_variant(&flavour, &index)?;
+                let (variant, payload) = &flavour.variants[position];
+                if !payload.is_empty() {
+                    return Err(RuntimeError::ArgumentCount {
+                        identifier: variant.clone(),
+                        expected: payload.len(),
+                        found: 0,
+                    });
                 }
+                Ok(Value::Variant(flavour, position, vec![]))
             }
-            _ => panic!("for loop is not iteratable"),
+            value => Err(RuntimeError::TypeMismatch {
+                expected: "array, string, pantry or dish",
+                found: value,
+            }),
         }
-        None
     }
 
-    fn execute_plate(&mut self, token: Token) -> Option<Value> {
-        match token {
-            Token::Identifier(identifier) => {
-                let value = self.state.get_variable(identifier)?;
-                match value {
-                    Value::ArrayNumber(array) => {
-                        println!("{:?}", array);
-                    }
-                    Value::ArrayString(array) => {
-                        println!("{:?}", array);
-                    }
-                    Value::Number(number) => {
-                        println!("{}", number);
-                    }
-                    Value::String(string) => {
-                        println!("{}", string);
+    fn execute_slice(
+        &self,
+        target: Value,
+        start: Option<Value>,
+        end: Option<Value>,
+    ) -> Result<Value, RuntimeError> {
+        let length = match &target {
+            Value::Array(array) => array.len(),
+            Value::String(string) => string.chars().count(),
+            _ => {
+                return Err(RuntimeError::TypeMismatch {
+                    expected: "array or string",
+                    found: target,
+                })
+            }
+        };
+        let start = match start {
+            Some(start) => resolve_index(&start, length, true)?,
+            None => 0,
+        };
+        let end = match end {
+            Some(end) => resolve_index(&end, length, true)?,
+            None => length,
+        };
+        let end = end.max(start);
+        match target {
+            Value::Array(array) => Ok(Value::Array(array[start..end].to_vec())),
+            Value::String(string) => Ok(Value::String(
+                string.chars().skip(start).take(end - start).collect(),
+            )),
+            _ => unreachable!(),
+        }
+    }
+
+    fn execute_index_assign(
+        &self,
+        target: Value,
+        indices: &[Value],
+        value: Value,
+    ) -> Result<Value, RuntimeError> {
+        let Some((index, rest)) = indices.split_first() else {
+            return Ok(value);
+        };
+        match target {
+            Value::Array(mut array) => {
+                let position = resolve_index(index, array.len(), false)?;
+                let element = mem::replace(&mut array[position], Value::Empty);
+                array[position] = self.execute_index_assign(element, rest, value)?;
+                Ok(Value::Array(array))
+            }
+            Value::Pantry(mut entries) => {
+                match entries.iter().position(|(key, _)| key == index) {
+                    Some(position) => {
+                        let element = mem::replace(&mut entries[position].1, Value::Empty);
+                        entries[position].1 = self.execute_index_assign(element, rest, value)?;
                     }
-                    Value::Function(params, tokens) => {
-                        println!("params: {:?}, tokens: {:?}", params, tokens);
+                    None if rest.is_empty() => entries.push((index.clone(), value)),
+                    None => return Err(RuntimeError::MissingKey { key: index.clone() }),
+                }
+                Ok(Value::Pantry(entries))
+            }
+            Value::String(string) => {
+                let mut chars: Vec<String> = string.chars().map(String::from).collect();
+                let position = resolve_index(index, chars.len(), false)?;
+                let element = Value::String(mem::take(&mut chars[position]));
+                chars[position] = match self.execute_index_assign(element, rest, value)? {
+                    Value::String(replacement) => replacement,
+                    value => {
+                        return Err(RuntimeError::TypeMismatch {
+                            expected: "string",
+                            found: value,
+                        })
                     }
-                    Value::Boolean(boolean) => {
-                        println!("{}", boolean);
+                };
+                Ok(Value::String(chars.concat()))
+            }
+            Value::Instance(dish, mut values) => {
+                let position = resolve_field(&dish, index)?;
+                let element = mem::replace(&mut values[position], Value::Empty);
+                values[position] = self.execute_index_assign(element, rest, value)?;
+                Ok(Value::Instance(dish, values))
+            }
+            value => Err(RuntimeError::TypeMismatch {
+                expected: "array, string, pantry or dish",
+                found: value,
+            }),
+        }
+    }
+
+    fn execute_block(
+        &mut self,
+        commands: &[Spanned<Command>],
+        scope: HashMap<String, Value>,
+    ) -> Result<Option<Signal>, RuntimeError> {
+        self.state.push_scope(scope);
+        let served = self.execute(commands);
+        self.state.pop_scope();
+        served
+    }
+
+    fn execute_layer(
+        &mut self,
+        label: &Option<String>,
+        condition: &Spanned<Expression>,
+        commands: &[Spanned<Command>],
+    ) -> Result<Option<Signal>, RuntimeError> {
+        while self.execute_condition(condition)? {
+            let signal = self.execute_block(commands, HashMap::new())?;
+            match signal {
+                Some(Signal::Break(target)) if targets(&target, label) => break,
+                Some(Signal::Continue(target)) if targets(&target, label) => continue,
+                Some(signal) => return Ok(Some(signal)),
+                None => {}
+            }
+        }
+        Ok(None)
+    }
+
+    fn execute_taste(
+        &mut self,
+        condition: &Spanned<Expression>,
+        commands: &[Spanned<Command>],
+        otherwise: &[Spanned<Command>],
+    ) -> Result<Option<Signal>, RuntimeError> {
+        if self.execute_condition(condition)? {
+            self.execute_block(commands, HashMap::new())
+        } else {
+            self.execute_block(otherwise, HashMap::new())
+        }
+    }
+
+    fn execute_condition(&mut self, condition: &Spanned<Expression>) -> Result<bool, RuntimeError> {
+        match self.execute_expression(condition)? {
+            Value::Boolean(boolean) => Ok(boolean),
+            value => {
+                self.error_span.get_or_insert(condition.span.clone());
+                Err(RuntimeError::TypeMismatch {
+                    expected: "boolean",
+                    found: value,
+                })
+            }
+        }
+    }
+
+    fn execute_comparison(
+        &self,
+        operator_1: Value,
+        comparison: &Token,
+        operator_2: Value,
+    ) -> Result<bool, RuntimeError> {
+        let ordering = match (&operator_1, &operator_2) {
+            (Value::Number(left), Value::Number(right)) => Some(left.cmp(right)),
+            (Value::Number(_) | Value::BigNumber(_), Value::Number(_) | Value::BigNumber(_)) => {
+                operator_1
+                    .to_integer()
+                    .zip(operator_2.to_integer())
+                    .map(|(left, right)| left.cmp(&right))
+            }
+            (Value::String(left), Value::String(right)) => Some(left.cmp(right)),
+            (Value::Boolean(left), Value::Boolean(right)) => Some(left.cmp(right)),
+            (left, right) => left
+                .to_float()
+                .zip(right.to_float())
+                .and_then(|(left, right)| left.partial_cmp(&right)),
+        };
+        let Some(ordering) = ordering else {
+            return match comparison {
+                Token::Same => Ok(operator_1 == operator_2),
+                Token::NotSame => Ok(operator_1 != operator_2),
+                _ => Err(RuntimeError::Incomparable {
+                    left: operator_1,
+                    right: operator_2,
+                }),
+            };
+        };
+        match comparison {
+            Token::Hotter => Ok(ordering == Ordering::Greater),
+            Token::Cooler => Ok(ordering == Ordering::Less),
+            Token::Same => Ok(ordering == Ordering::Equal),
+            Token::HotOrSame => Ok(ordering != Ordering::Less),
+            Token::CoolOrSame => Ok(ordering != Ordering::Greater),
+            Token::NotSame => Ok(ordering != Ordering::Equal),
+            token => Err(RuntimeError::InvalidCommand(token.clone())),
+        }
+    }
+
+    fn execute_simmer(
+        &mut self,
+        label: &Option<String>,
+        identifier: &str,
+        value_identifier: &Option<String>,
+        collection: &Spanned<Expression>,
+        commands: &[Spanned<Command>],
+    ) -> Result<Option<Signal>, RuntimeError> {
+        let value = self.execute_expression(collection)?;
+        let keyed = matches!(value, Value::Pantry(_));
+        let indices = (0..).map(Value::Number);
+        let entries: Box<dyn Iterator<Item = (Value, Value)>> = match value {
+            Value::Pantry(entries) => Box::new(entries.into_iter()),
+            Value::Array(array) => Box::new(indices.zip(array)),
+            Value::String(string) => {
+                let chars: Vec<Value> = string
+                    .chars()
+                    .map(|ch| Value::String(ch.to_string()))
+                    .collect();
+                Box::new(indices.zip(chars))
+            }
+            Value::Range(range) => Box::new(indices.zip(range.iter().map(Value::Number))),
+            value => {
+                self.error_span.get_or_insert(collection.span.clone());
+                return Err(RuntimeError::NotIterable { value });
+            }
+        };
+        for (key, value) in entries {
+            let scope = match value_identifier {
+                Some(value_identifier) => HashMap::from([
+                    (identifier.to_string(), key),
+                    (value_identifier.clone(), value),
+                ]),
+                None if keyed => HashMap::from([(identifier.to_string(), key)]),
+                None => HashMap::from([(identifier.to_string(), value)]),
+            };
+            let signal = self.execute_block(commands, scope)?;
+            match signal {
+                Some(Signal::Break(target)) if targets(&target, label) => break,
+                Some(Signal::Continue(target)) if targets(&target, label) => continue,
+                Some(signal) => return Ok(Some(signal)),
+                None => {}
+            }
+        }
+        Ok(None)
+    }
+
+    fn execute_cook(
+        &mut self,
+        commands: &[Spanned<Command>],
+        error: &str,
+        burnt: &[Spanned<Command>],
+    ) -> Result<Option<Signal>, RuntimeError> {
+        match self.execute_block(commands, HashMap::new()) {
+            Ok(served) => Ok(served),
+            Err(runtime_error) => {
+                self.error_span = None;
+                let scope = HashMap::from([(error.to_string(), runtime_error.to_value())]);
+                self.execute_block(burnt, scope)
+            }
+        }
+    }
+
+    fn execute_sift(
+        &mut self,
+        value: &Spanned<Expression>,
+        arms: &[Arm],
+    ) -> Result<Option<Signal>, RuntimeError> {
+        let span = value.span.clone();
+        let value = self.execute_expression(value)?;
+        for arm in arms {
+            let mut bindings = HashMap::new();
+            if !self.execute_pattern(&arm.pattern, &value, &mut bindings)? {
+                continue;
+            }
+            self.state.push_scope(bindings);
+            let guarded = match &arm.guard {
+                Some(guard) => self.execute_condition(guard),
+                None => Ok(true),
+            };
+            let result = match guarded {
+                Ok(true) => self.execute(&arm.commands).map(Some),
+                Ok(false) => Ok(None),
+                Err(error) => Err(error),
+            };
+            self.state.pop_scope();
+            if let Some(signal) = result? {
+                return Ok(signal);
+            }
+        }
+        self.error_span.get_or_insert(span);
+        Err(RuntimeError::NoMatch { value })
+    }
+
+    fn execute_pattern(
+        &mut self,
+        pattern: &Pattern,
+        value: &Value,
+        bindings: &mut HashMap<String, Value>,
+    ) -> Result<bool, RuntimeError> {
+        match (pattern, value) {
+            (Pattern::Wildcard, _) => Ok(true),
+            (Pattern::Binding(identifier), value) => {
+                bindings.insert(identifier.clone(), value.clone());
+                Ok(true)
+            }
+            (Pattern::Literal(literal), value) => {
+                let literal = self.execute_expression(literal)?;
+                if literal.to_float().is_some() && value.to_float().is_some() {
+                    return self.execute_comparison(literal, &Token::Same, value.clone());
+                }
+                Ok(literal == *value)
+            }
+            (
+                Pattern::Range {
+                    start,
+                    end,
+                    inclusive,
+                },
+                value,
+            ) => {
+                let Some(number) = value.to_float() else {
+                    return Ok(false);
+                };
+                let start = self.execute_bound(start)?;
+                let end = self.execute_bound(end)?;
+                Ok(start <= number
+                    && if *inclusive {
+                        number <= end
+                    } else {
+                        number < end
+                    })
+            }
+            (Pattern::Array { elements, rest }, Value::Array(array)) => {
+                let fits = match rest {
+                    Some(_) => array.len() >= elements.len(),
+                    None => array.len() == elements.len(),
+                };
+                if !fits {
+                    return Ok(false);
+                }
+                for (element, value) in elements.iter().zip(array) {
+                    if !self.execute_pattern(element, value, bindings)? {
+                        return Ok(false);
                     }
                 }
+                if let Some(rest) = rest.as_ref().filter(|rest| *rest != "_") {
+                    bindings.insert(rest.clone(), Value::Array(array[elements.len()..].to_vec()));
+                }
+                Ok(true)
             }
-            Token::StringLiteral(str) => {
-                println!("{}", str);
+            (Pattern::Pantry(patterns), Value::Pantry(entries)) => {
+                for (key, pattern) in patterns {
+                    let key = self.execute_expression(key)?;
+                    let Some((_, value)) = entries.iter().find(|(existing, _)| *existing == key)
+                    else {
+                        return Ok(false);
+                    };
+                    if !self.execute_pattern(pattern, value, bindings)? {
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
             }
-            Token::Number(number) => {
-                println!("{}", number);
+            (Pattern::Type { name, pattern }, value) if value.type_name() == name => {
+                self.execute_pattern(pattern, value, bindings)
             }
-            Token::ArrayNumber(array) => {
-                println!("{:?}", array);
+            (
+                Pattern::Variant {
+                    flavour,
+                    variant,
+                    payload,
+                },
+                value,
+            ) => {
+                let expected = match self.execute_expression(flavour)? {
+                    Value::Flavour(expected) => expected,
+                    found => {
+                        self.error_span.get_or_insert(flavour.span.clone());
+                        return Err(RuntimeError::TypeMismatch {
+                            expected: "flavour",
+                            found,
+                        });
+                    }
+                };
+                let position = resolve_variant(&expected, &Value::String(variant.clone()))
+                    .inspect_err(|_| {
+                        self.error_span.get_or_insert(flavour.span.clone());
+                    })?;
+                let Value::Variant(actual, index, values) = value else {
+                    return Ok(false);
+                };
+                if !Rc::ptr_eq(&expected, actual) || *index != position {
+                    return Ok(false);
+                }
+                let Some(payload) = payload else {
+                    return Ok(true);
+                };
+                if payload.len() != values.len() {
+                    return Ok(false);
+                }
+                for (pattern, value) in payload.iter().zip(values) {
+                    if !self.execute_pattern(pattern, value, bindings)? {
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
             }
-            Token::ArrayString(array) => {
-                println!("{:?}", array);
+            _ => Ok(false),
+        }
+    }
+
+    fn execute_bound(&mut self, bound: &Spanned<Expression>) -> Result<f64, RuntimeError> {
+        let value = self.execute_expression(bound)?;
+        value.to_float().ok_or_else(|| {
+            self.error_span.get_or_insert(bound.span.clone());
+            RuntimeError::TypeMismatch {
+                expected: "number",
+                found: value,
             }
-            _ => panic!("Invalid value passed for Plate"),
-        };
-        None
+        })
+    }
+
+    fn execute_burn(
+        &mut self,
+        value: &Spanned<Expression>,
+    ) -> Result<Option<Signal>, RuntimeError> {
+        let value = self.execute_expression(value)?;
+        Err(RuntimeError::Burnt(value))
+    }
+
+    fn execute_plate(
+        &mut self,
+        value: &Spanned<Expression>,
+    ) -> Result<Option<Signal>, RuntimeError> {
+        let value = self.execute_expression(value)?;
+        println!("{}", value);
+        Ok(None)
     }
 
     pub fn execute_recipe(
         &mut self,
-        identifier: String,
-        params: Vec<String>,
-        commands: Vec<Command>,
-    ) -> Option<Value> {
+        identifier: &String,
+        params: &[String],
+        commands: &[Spanned<Command>],
+    ) -> Result<Option<Signal>, RuntimeError> {
+        let recipe = Recipe {
+            params: params.to_vec(),
+            commands: commands.to_vec(),
+            environment: self.state.capture(),
+        };
         self.state
-            .add_variable(&identifier, Value::Function(params, commands))
+            .add_variable(identifier, Value::Function(Rc::new(recipe)));
+        Ok(None)
+    }
+
+    pub fn execute_dish(
+        &mut self,
+        identifier: &String,
+        fields: &[String],
+        methods: &[Method],
+    ) -> Result<Option<Signal>, RuntimeError> {
+        let methods = methods
+            .iter()
+            .map(|method| {
+                let recipe = Recipe {
+                    params: method.params.clone(),
+                    commands: method.commands.clone(),
+                    environment: self.state.capture(),
+                };
+                (method.identifier.clone(), Rc::new(recipe))
+            })
+            .collect();
+        let dish = Dish {
+            name: identifier.clone(),
+            fields: fields.to_vec(),
+            methods,
+        };
+        self.state
+            .add_variable(identifier, Value::Dish(Rc::new(dish)));
+        Ok(None)
+    }
+
+    pub fn execute_flavour(
+        &mut self,
+        identifier: &String,
+        variants: &[(String, Vec<String>)],
+    ) -> Result<Option<Signal>, RuntimeError> {
+        let flavour = Flavour {
+            name: identifier.clone(),
+            variants: variants.to_vec(),
+        };
+        self.state
+            .add_variable(identifier, Value::Flavour(Rc::new(flavour)));
+        Ok(None)
+    }
+}
+
+fn targets(target: &Option<String>, label: &Option<String>) -> bool {
+    target.is_none() || target == label
+}
+
+fn place(expression: &Spanned<Expression>) -> Option<(&String, Vec<&Spanned<Expression>>)> {
+    match &expression.node {
+        Expression::Identifier(identifier) => Some((identifier, vec![])),
+        Expression::Index { target, index } => {
+            let (identifier, mut indices) = place(target)?;
+            indices.push(index);
+            Some((identifier, indices))
+        }
+        _ => None,
+    }
+}
+
+fn resolve_index(index: &Value, length: usize, inclusive: bool) -> Result<usize, RuntimeError> {
+    let Value::Number(index) = *index else {
+        return Err(RuntimeError::TypeMismatch {
+            expected: "number",
+            found: index.clone(),
+        });
+    };
+    let position = if index < 0 {
+        index + length as isize
+    } else {
+        index
+    };
+    let limit = if inclusive {
+        length
+    } else {
+        length.saturating_sub(1)
+    };
+    if position < 0 || position as usize > limit || (!inclusive && length == 0) {
+        return Err(RuntimeError::IndexOutOfBounds { index, length });
+    }
+    Ok(position as usize)
+}
+
+fn resolve_field(dish: &Dish, field: &Value) -> Result<usize, RuntimeError> {
+    dish.fields
+        .iter()
+        .position(|name| matches!(field, Value::String(field) if field == name))
+        .ok_or_else(|| RuntimeError::NoField {
+            dish: dish.name.clone(),
+            field: field.clone(),
+        })
+}
+
+fn resolve_variant(flavour: &Flavour, variant: &Value) -> Result<usize, RuntimeError> {
+    flavour
+        .variants
+        .iter()
+        .position(|(name, _)| matches!(variant, Value::String(variant) if variant == name))
+        .ok_or_else(|| RuntimeError::NoVariant {
+            flavour: flavour.name.clone(),
+            variant: variant.clone(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{lexer::Lexer, parser::Parser};
+
+    fn run(code: &str) -> Result<Interpreter, RuntimeError> {
+        let tokens = Lexer::new(code, "test").lex().unwrap();
+        let commands = Parser::new(tokens).parse().unwrap();
+        let mut interpreter = Interpreter::new();
+        interpreter.execute(&commands)?;
+        Ok(interpreter)
+    }
+
+    fn variable(code: &str, name: &str) -> Value {
+        run(code)
+            .unwrap()
+            .state
+            .get_variable(name.to_string())
+            .unwrap()
+    }
+
+    #[test]
+    fn closures_keep_self_after_the_method_returns() {
+        let code = "
+            dish Box {
+                v
+                recipe getter() {
+                    recipe g() { serve self.v }
+                    serve g
+                }
+            }
+            ingredient b is Box(4)
+            ingredient g is b.getter()
+            ingredient x is g()
+        ";
+        assert_eq!(variable(code, "x"), Value::Number(4));
+    }
+
+    #[test]
+    fn methods_write_self_back_to_the_receiver() {
+        let code = "
+            dish Item {
+                name
+                recipe rename(n) { self.name is n }
+            }
+            ingredient menu is [Item(\"a\")]
+            menu[0].rename(\"b\")
+            ingredient x is menu[0].name
+        ";
+        assert_eq!(variable(code, "x"), Value::String("b".to_string()));
+    }
+
+    #[test]
+    fn same_compares_collections_by_value() {
+        let code = "
+            dish P { x }
+            ingredient a is [1, 2] same [1, 2]
+            ingredient b is pantry { \"k\" is [1] } notsame pantry { \"k\" is [2] }
+            ingredient c is P(1) same P(1)
+            ingredient d is 1 same \"1\"
+        ";
+        let mut interpreter = run(code).unwrap();
+        let mut get = |name: &str| interpreter.state.get_variable(name.to_string());
+        assert_eq!(get("a"), Some(Value::Boolean(true)));
+        assert_eq!(get("b"), Some(Value::Boolean(true)));
+        assert_eq!(get("c"), Some(Value::Boolean(true)));
+        assert_eq!(get("d"), Some(Value::Boolean(false)));
+        assert!(matches!(
+            run("ingredient e is [1] hotter [0]"),
+            Err(RuntimeError::Incomparable { .. })
+        ));
     }
 }
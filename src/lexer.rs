@@ -1,31 +1,148 @@
+use std::rc::Rc;
+
 use crate::{
-    tokens::Token,
-    utils::{MathsOperations, ValueType},
+    errors::LexError,
+    integers::BigInt,
+    spans::{Span, Spanned},
+    tokens::{StringPart, Token},
+    utils::MathsOperations,
 };
 
+pub const KEYWORDS: &[(&str, Token)] = &[
+    ("ingredient", Token::Ingredient),
+    ("taste", Token::Taste),
+    ("taste_again", Token::TasteAgain),
+    ("tasteless", Token::Tasteless),
+    ("layer", Token::Layer),
+    ("simmer", Token::Simmer),
+    ("cook", Token::Cook),
+    ("burnt", Token::Burnt),
+    ("burn", Token::Burn),
+    ("serve", Token::Serve),
+    ("break", Token::Break),
+    ("continue", Token::Continue),
+    ("plate", Token::Plate),
+    ("sift", Token::Sift),
+    ("when", Token::When),
+    ("recipe", Token::Recipe),
+    ("pantry", Token::Pantry),
+    ("dish", Token::Dish),
+    ("flavour", Token::Flavour),
+    ("open", Token::Fresh),
+    ("close", Token::Spoiled),
+    ("mix", Token::Mix),
+    ("separate", Token::Separate),
+    ("is", Token::Is),
+    ("in", Token::In),
+    ("to", Token::To),
+    ("until", Token::Until),
+    ("by", Token::By),
+    ("hotter", Token::Hotter),
+    ("cooler", Token::Cooler),
+    ("same", Token::Same),
+    ("hotorsame", Token::HotOrSame),
+    ("coolorsame", Token::CoolOrSame),
+    ("notsame", Token::NotSame),
+    ("not", Token::Not),
+];
+
+fn keyword(word: &str) -> Option<Token> {
+    KEYWORDS
+        .iter()
+        .find(|(keyword, _)| *keyword == word)
+        .map(|(_, token)| token.clone())
+}
+
+#[derive(Clone)]
+struct Location {
+    line: usize,
+    column: usize,
+    offset: usize,
+}
+
 pub struct Lexer {
     input: Vec<char>,
+    locations: Vec<Location>,
+    file: Rc<str>,
     position: usize,
+    errors: Vec<LexError>,
 }
 
 impl Lexer {
-    pub fn new(input: &str) -> Self {
+    pub fn new(input: &str, file: &str) -> Self {
+        let mut locations = vec![];
+        let mut location = Location {
+            line: 1,
+            column: 1,
+            offset: 0,
+        };
+        for ch in input.chars() {
+            locations.push(location.clone());
+            location.offset += ch.len_utf8();
+            if ch == '\n' {
+                location.line += 1;
+                location.column = 1;
+            } else {
+                location.column += 1;
+            }
+        }
+        locations.push(location);
         Self {
             input: input.chars().collect(),
+            locations,
+            file: file.into(),
             position: 0,
+            errors: vec![],
+        }
+    }
+
+    pub fn lex(&mut self) -> Result<Vec<Spanned<Token>>, Vec<LexError>> {
+        let tokens = self.lex_tokens();
+        if self.errors.is_empty() {
+            Ok(tokens)
+        } else {
+            Err(std::mem::take(&mut self.errors))
         }
     }
 
-    pub fn lex(&mut self) -> Vec<Token> {
+    pub fn lex_tokens(&mut self) -> Vec<Spanned<Token>> {
         let mut tokens = vec![];
-        while let Some(token) = self.next_token() {
-            if token != Token::Space {
-                tokens.push(token);
+        let mut start = self.position;
+        while let Some(ch) = self.curr_char() {
+            match self.next_token() {
+                Some(Token::Space) => {}
+                Some(token) => tokens.push(Spanned {
+                    node: token,
+                    span: self.span(start),
+                }),
+                None => {
+                    self.position = start + 1;
+                    self.errors.push(LexError::UnexpectedCharacter {
+                        character: ch,
+                        span: self.span(start),
+                    });
+                }
             }
+            start = self.position;
         }
         tokens
     }
 
+    pub fn span(&self, start: usize) -> Span {
+        self.span_to(start, self.position)
+    }
+
+    pub fn span_to(&self, start: usize, end: usize) -> Span {
+        let location = &self.locations[start];
+        Span {
+            file: self.file.clone(),
+            line: location.line,
+            column: location.column,
+            start: location.offset,
+            end: self.locations[end].offset,
+        }
+    }
+
     pub fn curr_char(&self) -> Option<char> {
         if self.input.len() <= self.position {
             return None;
@@ -47,203 +164,307 @@ impl Lexer {
         let ch = self.curr_char()?;
 
         match ch {
-            'i' if self.match_keyword("ingredient") => Some(Token::Ingredient),
-            't' if self.match_keyword("taste") => Some(Token::Taste),
-            't' if self.match_keyword("taste_again") => Some(Token::TasteAgain),
-            't' if self.match_keyword("tasteless") => Some(Token::Tasteless),
-            'l' if self.match_keyword("layer") => Some(Token::Layer),
-            's' if self.match_keyword("simmer") => Some(Token::Simmer),
-            'c' if self.match_keyword("cook") => Some(Token::Cook),
-            'b' if self.match_keyword("burnt") => Some(Token::Burnt),
-            's' if self.match_keyword("serve") => Some(Token::Serve),
-            'p' if self.match_keyword("plate") => Some(Token::Plate),
-            'r' if self.match_keyword("recipe") => Some(Token::Recipe),
-            'o' if self.match_keyword("open") => Some(Token::Fresh),
-            'c' if self.match_keyword("close") => Some(Token::Spoiled),
-            'm' if self.match_keyword("mix") => Some(Token::Mix),
-            's' if self.match_keyword("separate") => Some(Token::Separate),
-            'i' if self.match_keyword("is") => Some(Token::Is),
-            'i' if self.match_keyword("in") => Some(Token::In),
-            'h' if self.match_keyword("hotter") => Some(Token::Hotter),
-            'c' if self.match_keyword("cooler") => Some(Token::Cooler),
-            's' if self.match_keyword("same") => Some(Token::Same),
-            'h' if self.match_keyword("hotorsame") => Some(Token::HotOrSame),
-            'c' if self.match_keyword("coolorsame") => Some(Token::CoolOrSame),
-            'n' if self.match_keyword("notsame") => Some(Token::NotSame),
             '+' => self.parse_token(Token::Maths(MathsOperations::Plus)),
             '-' => self.parse_token(Token::Maths(MathsOperations::Minus)),
             '*' => self.parse_token(Token::Maths(MathsOperations::Multiply)),
             '/' => self.parse_token(Token::Maths(MathsOperations::Division)),
             '%' => self.parse_token(Token::Maths(MathsOperations::Mod)),
             '{' => self.parse_block(),
-            '[' => self.parse_array(),
-            '(' => self.parse_params(),
+            '[' => self.parse_token(Token::LeftBracket),
+            ']' => self.parse_token(Token::RightBracket),
+            '(' => self.parse_token(Token::LeftParen),
+            ')' => self.parse_token(Token::RightParen),
+            ',' => self.parse_token(Token::Comma),
+            ':' => self.parse_token(Token::Colon),
+            '.' if self.input.get(self.position + 1..self.position + 3) == Some(&['.', '.']) => {
+                self.advance(2);
+                self.parse_token(Token::Ellipsis)
+            }
+            '.' if self.input.get(self.position + 1) == Some(&'.') => {
+                self.advance(1);
+                self.parse_token(Token::DotDot)
+            }
+            '.' => self.parse_token(Token::Dot),
             ' ' => self.parse_token(Token::Space),
             '"' => self.parse_string_literal(),
-            '\n' => self.parse_token(Token::Space),
-            ch if ch.is_digit(10) => self.parse_number(),
-            ch if ch.is_alphanumeric() => self.parse_identifier(),
+            '#' => self.parse_comment(),
+            '\n' | '\t' | '\r' => self.parse_token(Token::Space),
+            ch if ch.is_ascii_digit() => self.parse_number(),
+            ch if ch.is_alphabetic() || ch == '_' => self.parse_identifier(),
             _ => None,
         }
     }
 
-    pub fn match_keyword(&mut self, keyword: &str) -> bool {
-        let mut end_pos = self.position;
-        while self.input[end_pos].is_alphabetic() {
-            end_pos += 1;
-            if (self.input.len() == end_pos) {
-                break;
+    pub fn parse_string_literal(&mut self) -> Option<Token> {
+        let start = self.position;
+        let mut parts = vec![];
+        let mut string = String::new();
+        self.advance(1);
+        loop {
+            match self.curr_char() {
+                None => {
+                    self.errors.push(LexError::UnterminatedString {
+                        span: self.span_to(start, start + 1),
+                    });
+                    break;
+                }
+                Some('"') => {
+                    self.advance(1);
+                    break;
+                }
+                Some('\\') => {
+                    let escape = self.position;
+                    self.advance(1);
+                    match self.parse_escape() {
+                        Some(ch) => string.push(ch),
+                        None => self.errors.push(LexError::InvalidEscape {
+                            sequence: self.input[escape..self.position].iter().collect(),
+                            span: self.span(escape),
+                        }),
+                    }
+                }
+                Some('{') => {
+                    if !string.is_empty() {
+                        parts.push(StringPart::Text(std::mem::take(&mut string)));
+                    }
+                    let open = self.position;
+                    let end = self.skip_code(open + 1);
+                    if end == self.input.len() {
+                        self.errors.push(LexError::UnterminatedInterpolation {
+                            span: self.span_to(open, open + 1),
+                        });
+                        self.position = end;
+                        return Some(Token::Interpolation(parts));
+                    }
+                    let tokens = self.lex_range(open + 1, end);
+                    self.position = (end + 1).min(self.input.len());
+                    parts.push(StringPart::Code(Spanned {
+                        node: tokens,
+                        span: self.span(open),
+                    }));
+                }
+                Some(ch) => {
+                    string.push(ch);
+                    self.advance(1);
+                }
             }
         }
-        if end_pos >= self.input.len() {
-            end_pos = self.input.len() - 1;
+        if parts.is_empty() {
+            return Some(Token::StringLiteral(string));
         }
-        if self.input[(self.position)..=end_pos - 1] == keyword.chars().collect::<Vec<_>>() {
-            self.position = end_pos;
-            return true;
+        if !string.is_empty() {
+            parts.push(StringPart::Text(string));
         }
-        false
+        Some(Token::Interpolation(parts))
     }
 
-    pub fn parse_string_literal(&mut self) -> Option<Token> {
-        let mut string = String::new();
+    pub fn parse_escape(&mut self) -> Option<char> {
+        let ch = self.curr_char()?;
         self.advance(1);
-        while let Some(ch) = self.next_char() {
-            if ch == '"' {
-                break;
+        match ch {
+            'n' => Some('\n'),
+            't' => Some('\t'),
+            'r' => Some('\r'),
+            '"' | '\\' | '{' | '}' => Some(ch),
+            'u' if self.curr_char() == Some('{') => {
+                self.advance(1);
+                let mut digits = String::new();
+                while let Some(ch) = self.curr_char() {
+                    self.advance(1);
+                    if ch == '}' {
+                        return u32::from_str_radix(&digits, 16)
+                            .ok()
+                            .and_then(char::from_u32);
+                    }
+                    if !ch.is_ascii_hexdigit() {
+                        return None;
+                    }
+                    digits.push(ch);
+                }
+                None
             }
-            string.push(ch);
+            _ => None,
         }
-        Some(Token::StringLiteral(string))
     }
 
-    pub fn parse_number(&mut self) -> Option<Token> {
-        let mut number = String::new();
+    pub fn parse_comment(&mut self) -> Option<Token> {
         while let Some(ch) = self.next_char() {
-            if ch.is_digit(10) {
-                number.push(ch);
-            } else {
+            if ch == '\n' {
                 break;
             }
         }
-        if number.is_empty() {
-            return None;
-        }
-        Some(Token::Number(number.parse().unwrap()))
+        Some(Token::Space)
     }
 
-    pub fn parse_identifier(&mut self) -> Option<Token> {
-        let mut identifier = String::new();
-        while let Some(ch) = self.next_char() {
-            if ch.is_alphanumeric() && ch != ' ' {
-                identifier.push(ch);
-            } else {
-                self.previous(1);
-                break;
+    pub fn parse_number(&mut self) -> Option<Token> {
+        let start = self.position;
+        let radix = match self.input.get(start..start + 2) {
+            Some(['0', 'x']) => 16,
+            Some(['0', 'o']) => 8,
+            Some(['0', 'b']) => 2,
+            _ => 10,
+        };
+        if radix != 10 {
+            self.advance(2);
+        }
+        let mut digits = String::new();
+        self.parse_digits(radix, &mut digits);
+        if digits.is_empty() {
+            while self
+                .curr_char()
+                .is_some_and(|ch| ch.is_alphanumeric() || ch == '_')
+            {
+                self.advance(1);
             }
+            self.errors.push(LexError::InvalidNumber {
+                literal: self.input[start..self.position].iter().collect(),
+                span: self.span(start),
+            });
+            return Some(Token::Number(0));
         }
-        if identifier.is_empty() {
-            return None;
+        if radix == 10 && (self.is_fraction() || self.is_exponent()) {
+            return self.parse_float(digits);
+        }
+        match isize::from_str_radix(&digits, radix) {
+            Ok(number) => Some(Token::Number(number)),
+            Err(_) => BigInt::parse(&digits, radix).map(Token::BigNumber),
         }
-        Some(Token::Identifier(identifier))
     }
 
-    pub fn parse_array(&mut self) -> Option<Token> {
-        self.next_char()?;
-        let ch = self.next_char()?;
-        let value_type = match ch {
-            '"' => ValueType::String,
-            _ => ValueType::Number,
-        };
-        self.position -= 1;
-        match value_type {
-            ValueType::String => self.parse_string_array(),
-            ValueType::Number => self.parse_number_array(),
+    pub fn parse_float(&mut self, mut literal: String) -> Option<Token> {
+        if self.is_fraction() {
+            literal.push('.');
+            self.advance(1);
+            self.parse_digits(10, &mut literal);
         }
+        if self.is_exponent() {
+            literal.push('e');
+            self.advance(1);
+            if let Some(sign @ ('+' | '-')) = self.curr_char() {
+                literal.push(sign);
+                self.advance(1);
+            }
+            self.parse_digits(10, &mut literal);
+        }
+        literal.parse().ok().map(Token::Float)
     }
 
-    pub fn parse_number_array(&mut self) -> Option<Token> {
-        let mut array = vec![];
-        let mut is_negative = false;
-        self.position -= 1;
-        while let Some(ch) = self.next_char() {
-            if ch == ']' {
+    pub fn parse_digits(&mut self, radix: u32, digits: &mut String) {
+        while let Some(ch) = self.curr_char() {
+            if ch.is_digit(radix) {
+                digits.push(ch);
+            } else if ch != '_' {
                 break;
-            } else if ch == '-' {
-                is_negative = true;
-            } else if ch.is_digit(10) {
-                let mut number_string = String::new();
-                if is_negative {
-                    number_string.push('-');
-                }
-                number_string.push(ch);
-                array.push(number_string);
-            }
-            if ch != '-' {
-                is_negative = false;
             }
+            self.advance(1);
         }
-        Some(Token::ArrayNumber(
-            array.iter().map(|s| s.parse().unwrap()).collect(),
-        ))
     }
 
-    pub fn parse_string_array(&mut self) -> Option<Token> {
-        let mut array = vec![];
-        let mut is_opened = false;
-        let mut curr_str = String::new();
-        while let Some(ch) = self.next_char() {
-            if ch == ']' {
+    fn is_fraction(&self) -> bool {
+        self.curr_char() == Some('.') && self.is_digit_at(self.position + 1)
+    }
+
+    fn is_exponent(&self) -> bool {
+        matches!(self.curr_char(), Some('e' | 'E'))
+            && (self.is_digit_at(self.position + 1)
+                || matches!(self.input.get(self.position + 1), Some('+' | '-'))
+                    && self.is_digit_at(self.position + 2))
+    }
+
+    fn is_digit_at(&self, position: usize) -> bool {
+        self.input
+            .get(position)
+            .is_some_and(|ch| ch.is_ascii_digit())
+    }
+
+    pub fn parse_identifier(&mut self) -> Option<Token> {
+        let mut identifier = String::new();
+        while let Some(ch) = self.curr_char() {
+            if ch.is_alphanumeric() || ch == '_' {
+                identifier.push(ch);
+                self.advance(1);
+            } else {
                 break;
-            } else if ch == '"' || (is_opened && ch == ',') {
-                if is_opened {
-                    array.push(curr_str.clone());
-                    curr_str.clear();
-                }
-                is_opened = !is_opened
-            } else if is_opened {
-                curr_str.push(ch);
             }
         }
-        Some(Token::ArrayString(array))
+        if identifier.is_empty() {
+            return None;
+        }
+        Some(keyword(&identifier).unwrap_or(Token::Identifier(identifier)))
     }
 
     pub fn parse_block(&mut self) -> Option<Token> {
-        let mut block = String::new();
-        self.advance(1);
-        while let Some(ch) = self.next_char() {
-            if ch == '}' {
-                break;
-            }
-            block.push(ch);
+        let open = self.position;
+        let end = self.skip_code(open + 1);
+        let reported = self.errors.len();
+        let tokens = self.lex_range(open + 1, end);
+        // A string or block left open inside the body already explains why
+        // the input ran out, so the block itself is not reported again.
+        let nested = self.errors[reported..]
+            .iter()
+            .any(LexError::is_unterminated);
+        if end == self.input.len() && !nested {
+            self.errors.insert(
+                reported,
+                LexError::UnterminatedBlock {
+                    span: self.span_to(open, open + 1),
+                },
+            );
         }
-        let tokens = Lexer::new(&block).lex();
+        self.position = (end + 1).min(self.input.len());
         Some(Token::Block(tokens))
     }
 
-    pub fn parse_params(&mut self) -> Option<Token> {
-        let mut params = vec![];
-        let mut curr_identifier = String::new();
-        self.advance(1);
-        while let Some(ch) = self.next_char() {
-            if ch == ' ' {
-                panic!("Spaces are not allowed inside params")
-            } else if ch == ',' || ch == ')' {
-                params.push(curr_identifier);
-                curr_identifier = String::new();
-            } else {
-                curr_identifier.push(ch);
+    fn skip_code(&self, mut position: usize) -> usize {
+        let mut depth = 1;
+        while let Some(ch) = self.input.get(position) {
+            match ch {
+                '"' => {
+                    position = self.skip_string(position);
+                    continue;
+                }
+                '#' => {
+                    while self.input.get(position).is_some_and(|ch| *ch != '\n') {
+                        position += 1;
+                    }
+                    continue;
+                }
+                '{' => depth += 1,
+                '}' => depth -= 1,
+                _ => {}
             }
-            if ch == ')' {
-                break;
+            if depth == 0 {
+                return position;
             }
+            position += 1;
         }
-        Some(Token::ArrayString(params))
+        self.input.len()
     }
 
-    pub fn parse_space(&mut self) -> Option<Token> {
-        self.advance(1);
-        Some(Token::Space)
+    fn skip_string(&self, mut position: usize) -> usize {
+        position += 1;
+        while let Some(ch) = self.input.get(position) {
+            match ch {
+                '"' => return position + 1,
+                '\\' => position += 2,
+                '{' => position = self.skip_code(position + 1) + 1,
+                _ => position += 1,
+            }
+        }
+        self.input.len()
+    }
+
+    fn lex_range(&mut self, start: usize, end: usize) -> Vec<Spanned<Token>> {
+        let mut lexer = Lexer {
+            input: self.input[start..end].to_vec(),
+            locations: self.locations[start..=end].to_vec(),
+            file: self.file.clone(),
+            position: 0,
+            errors: vec![],
+        };
+        let tokens = lexer.lex_tokens();
+        self.errors.append(&mut lexer.errors);
+        tokens
     }
 
     pub fn parse_token(&mut self, token: Token) -> Option<Token> {
@@ -258,12 +479,133 @@ impl Lexer {
             panic!("Cannot advance more");
         }
     }
+}
 
-    pub fn previous(&mut self, decrement: usize) {
-        if self.position - decrement >= 0 {
-            self.position -= decrement;
-        } else {
-            panic!("Cannot go back");
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(code: &str) -> Vec<Token> {
+        let tokens = Lexer::new(code, "test").lex().unwrap();
+        tokens.into_iter().map(|token| token.node).collect()
+    }
+
+    fn errors(code: &str) -> Vec<String> {
+        let errors = Lexer::new(code, "test").lex().unwrap_err();
+        errors.iter().map(|error| error.to_string()).collect()
+    }
+
+    fn nodes(tokens: &[Spanned<Token>]) -> Vec<Token> {
+        tokens.iter().map(|token| token.node.clone()).collect()
+    }
+
+    #[test]
+    fn lexes_nested_blocks() {
+        let lexed = tokens("taste open { plate \"}\" # }\n { plate 1 } }");
+        assert_eq!(lexed[..2], [Token::Taste, Token::Fresh]);
+        let Token::Block(body) = &lexed[2] else {
+            panic!("expected a block, found {:?}", lexed[2]);
+        };
+        assert_eq!(
+            nodes(&body[..2]),
+            [Token::Plate, Token::StringLiteral("}".to_string())]
+        );
+        let Token::Block(inner) = &body[2].node else {
+            panic!("expected a block, found {:?}", body[2].node);
+        };
+        assert_eq!(nodes(inner), [Token::Plate, Token::Number(1)]);
+        assert_eq!(errors("taste open {\n plate 1\n"), ["Unterminated block"]);
+    }
+
+    #[test]
+    fn separates_keywords_from_identifiers() {
+        assert_eq!(
+            tokens("taste_again tasteless inside tastes _in notsame"),
+            [
+                Token::TasteAgain,
+                Token::Tasteless,
+                Token::Identifier("inside".to_string()),
+                Token::Identifier("tastes".to_string()),
+                Token::Identifier("_in".to_string()),
+                Token::NotSame,
+            ]
+        );
+    }
+
+    #[test]
+    fn lexes_numbers() {
+        assert_eq!(
+            tokens("[12, -34]"),
+            [
+                Token::LeftBracket,
+                Token::Number(12),
+                Token::Comma,
+                Token::Maths(MathsOperations::Minus),
+                Token::Number(34),
+                Token::RightBracket,
+            ]
+        );
+        assert_eq!(
+            tokens("1_000 0xff 0o17 0b101 2.5e3"),
+            [
+                Token::Number(1000),
+                Token::Number(255),
+                Token::Number(15),
+                Token::Number(5),
+                Token::Float(2500.0),
+            ]
+        );
+        assert_eq!(
+            tokens("123456789012345678901234567890"),
+            [Token::BigNumber(
+                BigInt::parse("123456789012345678901234567890", 10).unwrap()
+            )]
+        );
+    }
+
+    #[test]
+    fn reports_invalid_numbers() {
+        assert_eq!(errors("plate 0x_"), ["Invalid number literal `0x_`"]);
+        assert_eq!(errors("plate 0bz + 1"), ["Invalid number literal `0bz`"]);
+    }
+
+    #[test]
+    fn lexes_escapes() {
+        assert_eq!(
+            tokens(r#""a\n\t\"\\\{\}\u{41}""#),
+            [Token::StringLiteral("a\n\t\"\\{}A".to_string())]
+        );
+        assert_eq!(errors(r#""\q""#), ["Invalid escape sequence `\\q`"]);
+    }
+
+    #[test]
+    fn lexes_interpolation() {
+        let lexed = tokens("\"a {x + 1} b\"");
+        let [Token::Interpolation(parts)] = &lexed[..] else {
+            panic!("expected an interpolation, found {:?}", lexed);
+        };
+        let [StringPart::Text(before), StringPart::Code(code), StringPart::Text(after)] =
+            &parts[..]
+        else {
+            panic!("expected text around code, found {:?}", parts);
+        };
+        assert_eq!((before.as_str(), after.as_str()), ("a ", " b"));
+        assert_eq!(
+            nodes(&code.node),
+            [
+                Token::Identifier("x".to_string()),
+                Token::Maths(MathsOperations::Plus),
+                Token::Number(1),
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_unclosed_interpolation_once() {
+        assert_eq!(
+            errors("layer i in 1 to 2 {\n    plate \"{\"\n}\n"),
+            ["Unterminated string interpolation"]
+        );
+        assert_eq!(errors("plate \"open"), ["Unterminated string"]);
     }
 }
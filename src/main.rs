@@ -1,21 +1,43 @@
-use std::{env, fs, os::unix::process};
+use std::{
+    env, fs,
+    io::{self, IsTerminal},
+    panic, process, thread,
+};
 
+use diagnostics::Diagnostic;
 use interpreter::Interpreter;
 use lexer::Lexer;
 use parser::Parser;
 
+mod builtins;
 mod commands;
+mod diagnostics;
+mod errors;
+mod expressions;
+mod integers;
 mod interpreter;
 mod lexer;
 mod parser;
+mod patterns;
+mod spans;
 mod state;
 mod tokens;
 mod utils;
 
+const STACK_SIZE: usize = 256 * 1024 * 1024;
+
 fn main() {
-    let args: Vec<String> = env::args().collect();
+    let interpreter = thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(run)
+        .expect("Unable to start the interpreter");
+    if let Err(payload) = interpreter.join() {
+        panic::resume_unwind(payload);
+    }
+}
 
-    println!("args, {:?}", args);
+fn run() {
+    let args: Vec<String> = env::args().collect();
 
     if args.len() < 2 {
         panic!("Usage: biryani filename");
@@ -25,16 +47,33 @@ fn main() {
 
     let code = fs::read_to_string(filename).expect("Unable to read the file");
 
-    let tokens = Lexer::new(&code).lex();
-
-    println!("tokens: {:?}", tokens);
+    let color = io::stderr().is_terminal() && env::var_os("NO_COLOR").is_none();
 
-    let commands = Parser::new(tokens).parse();
+    let tokens = match Lexer::new(&code, filename).lex() {
+        Ok(tokens) => tokens,
+        Err(errors) => report(errors.iter().map(|error| error.diagnostic()), &code, color),
+    };
 
-    println!("commands :{:?}", commands);
-
-    println!();
+    let commands = match Parser::new(tokens).parse() {
+        Ok(commands) => commands,
+        Err(errors) => report(errors.iter().map(|error| error.diagnostic()), &code, color),
+    };
 
     let mut interpreter = Interpreter::new();
-    interpreter.execute(commands);
+    if let Err(error) = interpreter.execute(&commands) {
+        match interpreter.error_span() {
+            Some(span) => report([error.diagnostic(span.clone())], &code, color),
+            None => {
+                eprintln!("Error: {}", error);
+                process::exit(1);
+            }
+        }
+    }
+}
+
+fn report(diagnostics: impl IntoIterator<Item = Diagnostic>, code: &str, color: bool) -> ! {
+    for diagnostic in diagnostics {
+        eprintln!("{}", diagnostic.render(code, color));
+    }
+    process::exit(1);
 }
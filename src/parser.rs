@@ -1,258 +1,1013 @@
-use core::num;
-
-use crate::{commands::Command, lexer::Lexer, tokens::Token, utils::MathsOperations};
+use crate::{
+    commands::{Command, Method},
+    errors::ParseError,
+    expressions::{Entry, Expression},
+    patterns::{Arm, Pattern, TYPE_NAMES},
+    spans::{Span, Spanned},
+    tokens::{StringPart, Token},
+    utils::MathsOperations,
+};
 
 pub struct Parser {
-    tokens: Vec<Token>,
+    tokens: Vec<Spanned<Token>>,
     position: usize,
+    errors: Vec<ParseError>,
+    loops: Vec<Option<String>>,
+    recipes: usize,
 }
 
 impl Parser {
-    pub fn new(tokens: Vec<Token>) -> Self {
+    pub fn new(tokens: Vec<Spanned<Token>>) -> Self {
         Self {
             tokens,
             position: 0,
+            errors: vec![],
+            loops: vec![],
+            recipes: 0,
         }
     }
 
-    pub fn parse(&mut self) -> Vec<Command> {
+    pub fn parse(&mut self) -> Result<Vec<Spanned<Command>>, Vec<ParseError>> {
+        let commands = self.parse_commands();
+        if self.errors.is_empty() {
+            Ok(commands)
+        } else {
+            Err(std::mem::take(&mut self.errors))
+        }
+    }
+
+    pub fn parse_commands(&mut self) -> Vec<Spanned<Command>> {
         let mut commands = vec![];
-        while let Some(command) = self.parse_token() {
-            commands.push(command);
+        while self.curr_token().is_some() {
+            let start = self.position;
+            match self.parse_token() {
+                Ok(command) => commands.push(self.spanned(start, command)),
+                Err(error) => {
+                    self.errors.push(error);
+                    self.synchronize(start);
+                }
+            }
         }
         commands
     }
 
-    pub fn parse_token(&mut self) -> Option<Command> {
-        let token = self.curr_token()?;
-        match token {
-            Token::Ingredient => self.parse_ingredient(),
-            Token::Taste => self.parse_taste(),
-            Token::Layer => self.parse_layer(),
-            Token::Simmer => self.parse_simmer(),
-            Token::Cook => self.parse_cook(),
-            Token::Serve => self.parse_serve(),
-            Token::Plate => self.parse_plate(),
-            Token::Recipe => self.parse_recipe(),
-            Token::Maths(_) => self.parse_maths(),
-            _ => None,
+    pub fn synchronize(&mut self, start: usize) {
+        if self.position == start {
+            self.advance();
+        }
+        while let Some(token) = self.curr_token() {
+            if is_statement_keyword(&token) {
+                break;
+            }
+            self.advance();
+        }
+    }
+
+    pub fn spanned<T>(&self, start: usize, node: T) -> Spanned<T> {
+        let span = self.tokens[start]
+            .span
+            .to(&self.tokens[self.position - 1].span);
+        Spanned { node, span }
+    }
+
+    pub fn error(&self, expected: &str) -> ParseError {
+        match self.tokens.get(self.position) {
+            Some(token) => ParseError {
+                expected: expected.to_string(),
+                found: Some(token.node.clone()),
+                span: token.span.clone(),
+            },
+            None => {
+                let last = &self.tokens[self.tokens.len() - 1].span;
+                ParseError {
+                    expected: expected.to_string(),
+                    found: None,
+                    span: Span {
+                        column: last.column + (last.end - last.start),
+                        start: last.end,
+                        ..last.clone()
+                    },
+                }
+            }
         }
     }
 
-    pub fn parse_ingredient(&mut self) -> Option<Command> {
-        self.expect_ingredient()?;
+    pub fn parse_token(&mut self) -> Result<Command, ParseError> {
+        match self.curr_token() {
+            Some(Token::Ingredient) => self.parse_ingredient(),
+            Some(Token::Taste) => self.parse_taste(),
+            Some(Token::Layer) => self.parse_layer(None),
+            Some(Token::Simmer) => self.parse_simmer(None),
+            Some(Token::Cook) => self.parse_cook(),
+            Some(Token::Burn) => self.parse_burn(),
+            Some(Token::Serve) => self.parse_serve(),
+            Some(Token::Break) | Some(Token::Continue) => self.parse_loop_control(),
+            Some(Token::Identifier(_)) if self.peek_token() == Some(Token::Colon) => {
+                self.parse_labelled()
+            }
+            Some(Token::Plate) => self.parse_plate(),
+            Some(Token::Sift) => self.parse_sift(),
+            Some(Token::Recipe) => self.parse_recipe(),
+            Some(Token::Dish) => self.parse_dish(),
+            Some(Token::Flavour) => self.parse_flavour(),
+            Some(Token::Identifier(_)) if self.peek_token() == Some(Token::Is) => {
+                self.parse_assign()
+            }
+            Some(Token::Identifier(_))
+                if matches!(self.peek_token(), Some(Token::LeftBracket | Token::Dot)) =>
+            {
+                self.parse_access()
+            }
+            Some(Token::Identifier(_)) if self.peek_token() == Some(Token::LeftParen) => {
+                self.parse_call()
+            }
+            _ => Err(self.error("a statement")),
+        }
+    }
+
+    pub fn parse_ingredient(&mut self) -> Result<Command, ParseError> {
+        self.expect_token(Token::Ingredient)?;
         let identifier = self.expect_identifier()?;
+        let indices = self.expect_indices()?;
         self.expect_token(Token::Is)?;
-        let value = self.expect_value()?;
-        let mut values = vec![value];
-        while let Some(token) = self.next_token() {
-            if let Token::Maths(_) = token {
-                values.push(token);
-            } else if let Token::Number(_) = token {
-                values.push(token);
-            } else if let Token::Identifier(_) = token {
-                values.push(token);
-            } else {
-                self.previous();
-                break;
+        let value = self.parse_expression()?;
+        if indices.is_empty() {
+            Ok(Command::Ingredient { identifier, value })
+        } else {
+            Ok(Command::Assign {
+                identifier,
+                indices,
+                value,
+            })
+        }
+    }
+
+    pub fn parse_assign(&mut self) -> Result<Command, ParseError> {
+        let identifier = self.expect_identifier()?;
+        let indices = self.expect_indices()?;
+        self.expect_token(Token::Is)?;
+        let value = self.parse_expression()?;
+        Ok(Command::Assign {
+            identifier,
+            indices,
+            value,
+        })
+    }
+
+    pub fn parse_access(&mut self) -> Result<Command, ParseError> {
+        let start = self.position;
+        let expression = self.parse_expression()?;
+        if let Some(Token::Is) = self.curr_token() {
+            self.position = start;
+            return self.parse_assign();
+        }
+        Ok(Command::Expression { expression })
+    }
+
+    pub fn parse_call(&mut self) -> Result<Command, ParseError> {
+        let expression = self.parse_expression()?;
+        Ok(Command::Expression { expression })
+    }
+
+    pub fn parse_expression(&mut self) -> Result<Spanned<Expression>, ParseError> {
+        let start = self.position;
+        let mut left = self.parse_mix()?;
+        while let Some(Token::Separate) = self.curr_token() {
+            self.advance();
+            let right = self.parse_mix()?;
+            left = self.spanned(
+                start,
+                Expression::Separate {
+                    left: Box::new(left),
+                    right: Box::new(right),
+                },
+            );
+        }
+        Ok(left)
+    }
+
+    pub fn parse_mix(&mut self) -> Result<Spanned<Expression>, ParseError> {
+        let start = self.position;
+        let mut left = self.parse_not()?;
+        while let Some(Token::Mix) = self.curr_token() {
+            self.advance();
+            let right = self.parse_not()?;
+            left = self.spanned(
+                start,
+                Expression::Mix {
+                    left: Box::new(left),
+                    right: Box::new(right),
+                },
+            );
+        }
+        Ok(left)
+    }
+
+    pub fn parse_not(&mut self) -> Result<Spanned<Expression>, ParseError> {
+        let start = self.position;
+        if let Some(Token::Not) = self.curr_token() {
+            self.advance();
+            let operand = self.parse_not()?;
+            return Ok(self.spanned(start, Expression::Not(Box::new(operand))));
+        }
+        self.parse_comparison()
+    }
+
+    pub fn parse_comparison(&mut self) -> Result<Spanned<Expression>, ParseError> {
+        let start = self.position;
+        let left = self.parse_range()?;
+        if !self.curr_token().as_ref().is_some_and(is_comparison) {
+            return Ok(left);
+        }
+        let comparison = self.expect_comparison()?;
+        let right = self.parse_range()?;
+        Ok(self.spanned(
+            start,
+            Expression::Comparison {
+                left: Box::new(left),
+                comparison,
+                right: Box::new(right),
+            },
+        ))
+    }
+
+    pub fn parse_range(&mut self) -> Result<Spanned<Expression>, ParseError> {
+        let start = self.position;
+        let left = self.parse_sum()?;
+        let inclusive = match self.curr_token() {
+            Some(Token::To) => true,
+            Some(Token::Until) => false,
+            _ => return Ok(left),
+        };
+        self.advance();
+        let right = self.parse_sum()?;
+        let step = match self.curr_token() {
+            Some(Token::By) => {
+                self.advance();
+                Some(Box::new(self.parse_sum()?))
             }
+            _ => None,
+        };
+        Ok(self.spanned(
+            start,
+            Expression::Range {
+                start: Box::new(left),
+                end: Box::new(right),
+                step,
+                inclusive,
+            },
+        ))
+    }
+
+    pub fn parse_sum(&mut self) -> Result<Spanned<Expression>, ParseError> {
+        let start = self.position;
+        let mut left = self.parse_term()?;
+        while let Some(Token::Maths(operation @ (MathsOperations::Plus | MathsOperations::Minus))) =
+            self.curr_token()
+        {
+            self.advance();
+            let right = self.parse_term()?;
+            left = self.spanned(
+                start,
+                Expression::Binary {
+                    left: Box::new(left),
+                    operation,
+                    right: Box::new(right),
+                },
+            );
         }
-        Some(Command::Ingredient { identifier, values })
+        Ok(left)
     }
 
-    pub fn parse_maths(&mut self) -> Option<Command> {
-        self.previous();
-        let mut values = vec![];
-        while let Some(token) = self.next_token() {
-            let value = match token {
-                Token::Number(number) => Some(Token::Number(number)),
-                Token::Identifier(identifier) => Some(Token::Identifier(identifier)),
-                Token::Maths(operation) => Some(Token::Maths(operation)),
-                _ => None,
+    pub fn parse_term(&mut self) -> Result<Spanned<Expression>, ParseError> {
+        let start = self.position;
+        let mut left = self.parse_unary()?;
+        while let Some(Token::Maths(
+            operation @ (MathsOperations::Multiply
+            | MathsOperations::Division
+            | MathsOperations::Mod),
+        )) = self.curr_token()
+        {
+            self.advance();
+            let right = self.parse_unary()?;
+            left = self.spanned(
+                start,
+                Expression::Binary {
+                    left: Box::new(left),
+                    operation,
+                    right: Box::new(right),
+                },
+            );
+        }
+        Ok(left)
+    }
+
+    pub fn parse_unary(&mut self) -> Result<Spanned<Expression>, ParseError> {
+        let start = self.position;
+        if let Some(Token::Maths(MathsOperations::Minus)) = self.curr_token() {
+            self.advance();
+            let operand = self.parse_unary()?;
+            return Ok(self.spanned(start, Expression::Negate(Box::new(operand))));
+        }
+        self.parse_postfix()
+    }
+
+    pub fn parse_postfix(&mut self) -> Result<Spanned<Expression>, ParseError> {
+        let start = self.position;
+        let mut target = self.parse_primary()?;
+        loop {
+            let expression = match self.curr_token() {
+                Some(Token::LeftBracket) => {
+                    self.advance();
+                    let expression = self.parse_subscript(Box::new(target))?;
+                    self.expect_token(Token::RightBracket)?;
+                    expression
+                }
+                Some(Token::LeftParen) => Expression::Call {
+                    callee: Box::new(target),
+                    arguments: self.expect_arguments()?,
+                },
+                Some(Token::Dot) => {
+                    let field = self.expect_field()?;
+                    match (&field.node, self.curr_token()) {
+                        (Expression::String(method), Some(Token::LeftParen)) => {
+                            Expression::MethodCall {
+                                target: Box::new(target),
+                                method: method.clone(),
+                                arguments: self.expect_arguments()?,
+                            }
+                        }
+                        _ => Expression::Index {
+                            target: Box::new(target),
+                            index: Box::new(field),
+                        },
+                    }
+                }
+                _ => break,
             };
-            if let Some(val) = value {
-                values.push(val);
-            } else {
-                break;
+            target = self.spanned(start, expression);
+        }
+        Ok(target)
+    }
+
+    pub fn parse_subscript(
+        &mut self,
+        target: Box<Spanned<Expression>>,
+    ) -> Result<Expression, ParseError> {
+        let start = match self.curr_token() {
+            Some(Token::DotDot) => None,
+            _ => Some(Box::new(self.parse_expression()?)),
+        };
+        match (start, self.curr_token()) {
+            (start, Some(Token::DotDot)) => {
+                self.advance();
+                let end = match self.curr_token() {
+                    Some(Token::RightBracket) => None,
+                    _ => Some(Box::new(self.parse_expression()?)),
+                };
+                Ok(Expression::Slice { target, start, end })
             }
+            (Some(index), _) => Ok(Expression::Index { target, index }),
+            (None, _) => Err(self.error("`..`")),
         }
-        Some(Command::Maths { values })
     }
 
-    pub fn parse_taste(&mut self) -> Option<Command> {
-        self.expect_token(Token::Taste)?;
-        let identifier_1 = self.expect_identifier()?;
-        let comparison = self.expect_comparison()?;
-        let identifier_2 = self.expect_identifier()?;
-        let block = self.expect_string_literal()?;
-        if let Token::StringLiteral(string) = block {
-            let mut lexer = Lexer::new(&string);
-            let tokens = lexer.lex();
-            return Some(Command::Taste {
-                identifier_1,
-                identifier_2,
-                comparison,
-                tokens,
-            });
+    pub fn parse_primary(&mut self) -> Result<Spanned<Expression>, ParseError> {
+        let start = self.position;
+        let expression = match self.curr_token() {
+            Some(Token::Number(number)) => Expression::Number(number),
+            Some(Token::BigNumber(number)) => Expression::BigNumber(number),
+            Some(Token::Float(float)) => Expression::Float(float),
+            Some(Token::StringLiteral(string)) => Expression::String(string),
+            Some(Token::Interpolation(parts)) => {
+                let span = self.tokens[self.position].span.clone();
+                let mut expressions = vec![];
+                for part in parts {
+                    expressions.push(match part {
+                        StringPart::Text(text) => Spanned {
+                            node: Expression::String(text),
+                            span: span.clone(),
+                        },
+                        StringPart::Code(code) => self.expect_interpolated(code)?,
+                    });
+                }
+                Expression::Interpolation(expressions)
+            }
+            Some(Token::Fresh) => Expression::Boolean(true),
+            Some(Token::Spoiled) => Expression::Boolean(false),
+            Some(Token::Identifier(identifier)) => {
+                self.advance();
+                return Ok(self.spanned(start, Expression::Identifier(identifier)));
+            }
+            Some(Token::Pantry) => {
+                self.advance();
+                let entries = self.expect_entries()?;
+                return Ok(self.spanned(start, Expression::Pantry(entries)));
+            }
+            Some(Token::LeftBracket) => {
+                let elements = self.expect_list(Token::LeftBracket, Token::RightBracket)?;
+                return Ok(self.spanned(start, Expression::Array(elements)));
+            }
+            Some(Token::LeftParen) => {
+                self.advance();
+                let expression = self.parse_expression()?;
+                self.expect_token(Token::RightParen)?;
+                return Ok(self.spanned(start, expression.node));
+            }
+            _ => return Err(self.error("a value")),
+        };
+        self.advance();
+        Ok(self.spanned(start, expression))
+    }
+
+    pub fn parse_taste(&mut self) -> Result<Command, ParseError> {
+        match self.curr_token() {
+            Some(Token::Taste) | Some(Token::TasteAgain) => self.advance(),
+            _ => return Err(self.error("`taste`")),
+        }
+        let condition = self.parse_expression()?;
+        let commands = self.parse_block()?;
+        let otherwise = match self.curr_token() {
+            Some(Token::TasteAgain) => {
+                let start = self.position;
+                let command = self.parse_taste()?;
+                vec![self.spanned(start, command)]
+            }
+            Some(Token::Tasteless) => {
+                self.advance();
+                self.parse_block()?
+            }
+            _ => vec![],
+        };
+        Ok(Command::Taste {
+            condition,
+            commands,
+            otherwise,
+        })
+    }
+
+    pub fn parse_labelled(&mut self) -> Result<Command, ParseError> {
+        let label = self.expect_identifier()?;
+        self.expect_token(Token::Colon)?;
+        match self.curr_token() {
+            Some(Token::Layer) => self.parse_layer(Some(label)),
+            Some(Token::Simmer) => self.parse_simmer(Some(label)),
+            _ => Err(self.error("`layer` or `simmer`")),
+        }
+    }
+
+    pub fn parse_loop_control(&mut self) -> Result<Command, ParseError> {
+        let keyword = self.curr_token();
+        if self.loops.is_empty() {
+            return Err(self.error("a loop around this statement"));
+        }
+        let line = self.tokens[self.position].span.line;
+        self.advance();
+        let label = match self.curr_token() {
+            Some(Token::Identifier(label)) if self.tokens[self.position].span.line == line => {
+                if !self.loops.contains(&Some(label.clone())) {
+                    return Err(self.error(&format!("a loop labelled `{}`", label)));
+                }
+                self.advance();
+                Some(label)
+            }
+            _ => None,
+        };
+        match keyword {
+            Some(Token::Break) => Ok(Command::Break { label }),
+            _ => Ok(Command::Continue { label }),
         }
-        None
     }
 
-    pub fn parse_layer(&mut self) -> Option<Command> {
+    pub fn parse_layer(&mut self, label: Option<String>) -> Result<Command, ParseError> {
         self.expect_token(Token::Layer)?;
-        let left_value = self.next_token()?;
-        let comparison = self.expect_comparison()?;
-        let right_value = self.next_token()?;
-        let tokens = self.expect_block()?;
-        let commands = Parser::new(tokens).parse();
-        Some(Command::Layer {
-            left_value,
-            right_value,
-            comparison,
+        let condition = self.parse_expression()?;
+        let commands = self.parse_loop_block(label.clone())?;
+        Ok(Command::Layer {
+            label,
+            condition,
             commands,
         })
     }
 
-    pub fn parse_simmer(&mut self) -> Option<Command> {
+    pub fn parse_simmer(&mut self, label: Option<String>) -> Result<Command, ParseError> {
         self.expect_token(Token::Simmer)?;
         let identifier = self.expect_identifier()?;
+        let value_identifier = match self.curr_token() {
+            Some(Token::Comma) => {
+                self.advance();
+                Some(self.expect_identifier()?)
+            }
+            _ => None,
+        };
         self.expect_token(Token::In)?;
-        let array_identifier = self.expect_identifier()?;
-        let tokens = self.expect_block()?;
-        let commands = Parser::new(tokens).parse();
-        Some(Command::Simmer {
+        let collection = self.parse_expression()?;
+        let commands = self.parse_loop_block(label.clone())?;
+        Ok(Command::Simmer {
+            label,
             identifier,
-            array_identifier,
+            value_identifier,
+            collection,
             commands,
         })
     }
 
-    pub fn parse_cook(&mut self) -> Option<Command> {
+    pub fn parse_cook(&mut self) -> Result<Command, ParseError> {
         self.expect_token(Token::Cook)?;
-        let block = self.expect_string_literal()?;
-        if let Token::StringLiteral(string) = block {
-            let mut lexer = Lexer::new(&string);
-            let tokens = lexer.lex();
-            return Some(Command::Cook { tokens });
-        }
-        None
+        let commands = self.parse_block()?;
+        self.expect_token(Token::Burnt)?;
+        let error = self.expect_identifier()?;
+        let burnt = self.parse_block()?;
+        Ok(Command::Cook {
+            commands,
+            error,
+            burnt,
+        })
+    }
+
+    pub fn parse_burn(&mut self) -> Result<Command, ParseError> {
+        self.expect_token(Token::Burn)?;
+        let value = self.parse_expression()?;
+        Ok(Command::Burn { value })
     }
 
-    pub fn parse_serve(&mut self) -> Option<Command> {
+    pub fn parse_serve(&mut self) -> Result<Command, ParseError> {
+        if self.recipes == 0 {
+            return Err(self.error("a recipe around this statement"));
+        }
         self.expect_token(Token::Serve)?;
-        let identifier = self.expect_identifier()?;
-        Some(Command::Serve { identifier })
+        let value = self.parse_expression()?;
+        Ok(Command::Serve { value })
     }
 
-    pub fn parse_plate(&mut self) -> Option<Command> {
+    pub fn parse_plate(&mut self) -> Result<Command, ParseError> {
         self.expect_token(Token::Plate)?;
-        let value = self.next_token()?;
-        Some(Command::Plate { value })
+        let value = self.parse_expression()?;
+        Ok(Command::Plate { value })
     }
 
-    pub fn parse_recipe(&mut self) -> Option<Command> {
+    pub fn parse_sift(&mut self) -> Result<Command, ParseError> {
+        self.expect_token(Token::Sift)?;
+        let value = self.parse_expression()?;
+        let tokens = self.expect_block()?;
+        let mut parser = Parser::new(tokens);
+        parser.loops = self.loops.clone();
+        parser.recipes = self.recipes;
+        let mut arms = vec![];
+        while parser.curr_token().is_some() {
+            let arm = parser.parse_arm();
+            self.errors.append(&mut parser.errors);
+            arms.push(arm?);
+        }
+        Ok(Command::Sift { value, arms })
+    }
+
+    pub fn parse_arm(&mut self) -> Result<Arm, ParseError> {
+        self.expect_token(Token::When)?;
+        let pattern = self.parse_pattern()?;
+        let guard = match self.curr_token() {
+            Some(Token::Taste) => {
+                self.advance();
+                Some(self.parse_expression()?)
+            }
+            _ => None,
+        };
+        let commands = self.parse_block()?;
+        Ok(Arm {
+            pattern,
+            guard,
+            commands,
+        })
+    }
+
+    pub fn parse_pattern(&mut self) -> Result<Pattern, ParseError> {
+        match self.curr_token() {
+            Some(Token::Identifier(identifier)) if identifier == "_" => {
+                self.advance();
+                Ok(Pattern::Wildcard)
+            }
+            Some(Token::Identifier(identifier)) if self.peek_token() == Some(Token::LeftParen) => {
+                self.parse_type_pattern(identifier)
+            }
+            Some(Token::Recipe) => self.parse_type_pattern("recipe".to_string()),
+            Some(Token::Dish) => self.parse_type_pattern("dish".to_string()),
+            Some(Token::Flavour) => self.parse_type_pattern("flavour".to_string()),
+            Some(Token::Identifier(_)) if self.peek_token() == Some(Token::Dot) => {
+                self.parse_variant_pattern()
+            }
+            Some(Token::Pantry) if self.peek_token() == Some(Token::LeftParen) => {
+                self.parse_type_pattern("pantry".to_string())
+            }
+            Some(Token::Identifier(identifier)) => {
+                self.advance();
+                Ok(Pattern::Binding(identifier))
+            }
+            Some(Token::LeftBracket) => self.parse_array_pattern(),
+            Some(Token::Pantry) => {
+                self.advance();
+                let tokens = self.expect_block()?;
+                let mut parser = Parser::new(tokens);
+                let mut entries = vec![];
+                while parser.curr_token().is_some() {
+                    let key = parser.parse_primary()?;
+                    parser.expect_token(Token::Is)?;
+                    entries.push((key, parser.parse_pattern()?));
+                    match parser.curr_token() {
+                        Some(Token::Comma) => parser.advance(),
+                        None => break,
+                        _ => return Err(parser.error("`,` or `}`")),
+                    }
+                }
+                Ok(Pattern::Pantry(entries))
+            }
+            Some(
+                Token::Number(_)
+                | Token::BigNumber(_)
+                | Token::Float(_)
+                | Token::StringLiteral(_)
+                | Token::Fresh
+                | Token::Spoiled
+                | Token::Maths(MathsOperations::Minus),
+            ) => {
+                let start = self.parse_unary()?;
+                let inclusive = match self.curr_token() {
+                    Some(Token::To) => true,
+                    Some(Token::Until) => false,
+                    _ => return Ok(Pattern::Literal(start)),
+                };
+                self.advance();
+                let end = self.parse_unary()?;
+                Ok(Pattern::Range {
+                    start,
+                    end,
+                    inclusive,
+                })
+            }
+            _ => Err(self.error("a pattern")),
+        }
+    }
+
+    pub fn parse_type_pattern(&mut self, name: String) -> Result<Pattern, ParseError> {
+        if !TYPE_NAMES.contains(&name.as_str()) {
+            return Err(self.error("a type name"));
+        }
+        self.advance();
+        self.expect_token(Token::LeftParen)?;
+        let pattern = self.parse_pattern()?;
+        self.expect_token(Token::RightParen)?;
+        Ok(Pattern::Type {
+            name,
+            pattern: Box::new(pattern),
+        })
+    }
+
+    pub fn parse_variant_pattern(&mut self) -> Result<Pattern, ParseError> {
+        let start = self.position;
+        let flavour = self.expect_identifier()?;
+        let flavour = self.spanned(start, Expression::Identifier(flavour));
+        self.expect_token(Token::Dot)?;
+        let variant = self.expect_identifier()?;
+        if self.curr_token() != Some(Token::LeftParen) {
+            return Ok(Pattern::Variant {
+                flavour,
+                variant,
+                payload: None,
+            });
+        }
+        self.advance();
+        let mut payload = vec![];
+        while self.curr_token() != Some(Token::RightParen) {
+            payload.push(self.parse_pattern()?);
+            match self.curr_token() {
+                Some(Token::Comma) => self.advance(),
+                _ => break,
+            }
+        }
+        self.expect_token(Token::RightParen)?;
+        Ok(Pattern::Variant {
+            flavour,
+            variant,
+            payload: Some(payload),
+        })
+    }
+
+    pub fn parse_array_pattern(&mut self) -> Result<Pattern, ParseError> {
+        self.expect_token(Token::LeftBracket)?;
+        let mut elements = vec![];
+        let mut rest = None;
+        while self.curr_token() != Some(Token::RightBracket) {
+            if let Some(Token::Ellipsis) = self.curr_token() {
+                self.advance();
+                rest = Some(self.expect_identifier()?);
+                break;
+            }
+            elements.push(self.parse_pattern()?);
+            match self.curr_token() {
+                Some(Token::Comma) => self.advance(),
+                _ => break,
+            }
+        }
+        self.expect_token(Token::RightBracket)?;
+        Ok(Pattern::Array { elements, rest })
+    }
+
+    pub fn parse_recipe(&mut self) -> Result<Command, ParseError> {
+        let Method {
+            identifier,
+            params,
+            commands,
+        } = self.parse_method()?;
+        Ok(Command::Recipe {
+            identifier,
+            params,
+            commands,
+        })
+    }
+
+    pub fn parse_method(&mut self) -> Result<Method, ParseError> {
         self.expect_token(Token::Recipe)?;
         let identifier = self.expect_identifier()?;
         let params = self.expect_params()?;
-        let tokens = self.expect_block()?;
-        let commands = Parser::new(tokens).parse();
-        Some(Command::Recipe {
+        let loops = std::mem::take(&mut self.loops);
+        self.recipes += 1;
+        let commands = self.parse_block();
+        self.recipes -= 1;
+        self.loops = loops;
+        let commands = commands?;
+        Ok(Method {
             identifier,
             params,
             commands,
         })
     }
 
-    pub fn expect_block(&mut self) -> Option<Vec<Token>> {
-        if let Some(Token::Block(tokens)) = self.next_token() {
-            Some(tokens)
-        } else {
-            None
+    pub fn parse_dish(&mut self) -> Result<Command, ParseError> {
+        self.expect_token(Token::Dish)?;
+        let identifier = self.expect_identifier()?;
+        let tokens = self.expect_block()?;
+        let mut parser = Parser::new(tokens);
+        let mut fields = vec![];
+        let mut methods = vec![];
+        while let Some(token) = parser.curr_token() {
+            match token {
+                Token::Recipe => {
+                    let method = parser.parse_method();
+                    self.errors.append(&mut parser.errors);
+                    methods.push(method?);
+                }
+                Token::Identifier(field) => {
+                    parser.advance();
+                    fields.push(field);
+                    if let Some(Token::Comma) = parser.curr_token() {
+                        parser.advance();
+                    }
+                }
+                _ => return Err(parser.error("a field or `recipe`")),
+            }
         }
+        Ok(Command::Dish {
+            identifier,
+            fields,
+            methods,
+        })
     }
 
-    pub fn expect_ingredient(&mut self) -> Option<Token> {
-        if let Some(Token::Ingredient) = self.next_token() {
-            Some(Token::Ingredient)
-        } else {
-            None
+    pub fn parse_flavour(&mut self) -> Result<Command, ParseError> {
+        self.expect_token(Token::Flavour)?;
+        let identifier = self.expect_identifier()?;
+        let tokens = self.expect_block()?;
+        let mut parser = Parser::new(tokens);
+        let mut variants = vec![];
+        while parser.curr_token().is_some() {
+            let variant = parser.expect_identifier()?;
+            let payload = match parser.curr_token() {
+                Some(Token::LeftParen) => parser.expect_params()?,
+                _ => vec![],
+            };
+            variants.push((variant, payload));
+            if let Some(Token::Comma) = parser.curr_token() {
+                parser.advance();
+            }
         }
+        Ok(Command::Flavour {
+            identifier,
+            variants,
+        })
     }
 
-    pub fn expect_identifier(&mut self) -> Option<String> {
-        if let Some(Token::Identifier(identifier)) = self.next_token() {
-            Some(identifier)
-        } else {
-            None
+    pub fn parse_block(&mut self) -> Result<Vec<Spanned<Command>>, ParseError> {
+        let tokens = self.expect_block()?;
+        let mut parser = Parser::new(tokens);
+        parser.loops = self.loops.clone();
+        parser.recipes = self.recipes;
+        let commands = parser.parse_commands();
+        self.errors.append(&mut parser.errors);
+        Ok(commands)
+    }
+
+    pub fn parse_loop_block(
+        &mut self,
+        label: Option<String>,
+    ) -> Result<Vec<Spanned<Command>>, ParseError> {
+        self.loops.push(label);
+        let commands = self.parse_block();
+        self.loops.pop();
+        commands
+    }
+
+    pub fn expect_block(&mut self) -> Result<Vec<Spanned<Token>>, ParseError> {
+        match self.curr_token() {
+            Some(Token::Block(tokens)) => {
+                self.advance();
+                Ok(tokens)
+            }
+            _ => Err(self.error("a block")),
         }
     }
 
-    pub fn expect_value(&mut self) -> Option<Token> {
-        let token = self.next_token()?;
-        match token {
-            Token::ArrayNumber(array) => Some(Token::ArrayNumber(array)),
-            Token::ArrayString(array) => Some(Token::ArrayString(array)),
-            Token::Number(number) => Some(Token::Number(number)),
-            Token::StringLiteral(string) => Some(Token::StringLiteral(string)),
-            Token::Identifier(identifier) => Some(Token::Identifier(identifier)),
-            _ => None,
+    pub fn expect_entries(&mut self) -> Result<Vec<Entry>, ParseError> {
+        let tokens = self.expect_block()?;
+        let mut parser = Parser::new(tokens);
+        let mut entries = vec![];
+        while parser.curr_token().is_some() {
+            let key = parser.parse_expression()?;
+            parser.expect_token(Token::Is)?;
+            let value = parser.parse_expression()?;
+            entries.push((key, value));
+            match parser.curr_token() {
+                Some(Token::Comma) => parser.advance(),
+                None => break,
+                _ => return Err(parser.error("`,` or `}`")),
+            }
         }
+        Ok(entries)
     }
 
-    pub fn expect_comparison(&mut self) -> Option<Token> {
-        let token = self.next_token()?;
-        match token {
-            Token::Hotter => Some(Token::Hotter),
-            Token::Cooler => Some(Token::Cooler),
-            Token::Same => Some(Token::Same),
-            Token::HotOrSame => Some(Token::HotOrSame),
-            Token::CoolOrSame => Some(Token::CoolOrSame),
-            Token::NotSame => Some(Token::NotSame),
-            _ => None,
+    pub fn expect_interpolated(
+        &mut self,
+        code: Spanned<Vec<Spanned<Token>>>,
+    ) -> Result<Spanned<Expression>, ParseError> {
+        if code.node.is_empty() {
+            return Err(ParseError {
+                expected: "an expression".to_string(),
+                found: None,
+                span: code.span,
+            });
+        }
+        let mut parser = Parser::new(code.node);
+        let expression = parser.parse_expression()?;
+        match parser.curr_token() {
+            Some(_) => Err(parser.error("`}`")),
+            None => Ok(expression),
         }
     }
 
-    pub fn expect_token(&mut self, expected_token: Token) -> Option<Token> {
-        if let Some(token) = self.next_token() {
-            if expected_token == token {
-                return Some(token);
+    pub fn expect_indices(&mut self) -> Result<Vec<Spanned<Expression>>, ParseError> {
+        let mut indices = vec![];
+        loop {
+            match self.curr_token() {
+                Some(Token::LeftBracket) => {
+                    self.advance();
+                    indices.push(self.parse_expression()?);
+                    self.expect_token(Token::RightBracket)?;
+                }
+                Some(Token::Dot) => indices.push(self.expect_field()?),
+                _ => return Ok(indices),
             }
-            None
-        } else {
-            None
         }
     }
 
-    pub fn expect_string_literal(&mut self) -> Option<Token> {
-        if let Some(Token::StringLiteral(string)) = self.next_token() {
-            return Some(Token::StringLiteral(string));
+    pub fn expect_field(&mut self) -> Result<Spanned<Expression>, ParseError> {
+        self.expect_token(Token::Dot)?;
+        let start = self.position;
+        let field = self.expect_identifier()?;
+        Ok(self.spanned(start, Expression::String(field)))
+    }
+
+    pub fn expect_identifier(&mut self) -> Result<String, ParseError> {
+        match self.curr_token() {
+            Some(Token::Identifier(identifier)) => {
+                self.advance();
+                Ok(identifier)
+            }
+            _ => Err(self.error("an identifier")),
+        }
+    }
+
+    pub fn expect_arguments(&mut self) -> Result<Vec<Spanned<Expression>>, ParseError> {
+        self.expect_list(Token::LeftParen, Token::RightParen)
+    }
+
+    pub fn expect_list(
+        &mut self,
+        open: Token,
+        close: Token,
+    ) -> Result<Vec<Spanned<Expression>>, ParseError> {
+        self.expect_token(open)?;
+        let mut elements = vec![];
+        if self.curr_token() == Some(close.clone()) {
+            self.advance();
+            return Ok(elements);
         }
-        None
+        loop {
+            elements.push(self.parse_expression()?);
+            match self.curr_token() {
+                Some(Token::Comma) => self.advance(),
+                Some(token) if token == close => {
+                    self.advance();
+                    break;
+                }
+                _ => return Err(self.error(&format!("`,` or {}", close))),
+            }
+        }
+        Ok(elements)
     }
 
-    pub fn expect_params(&mut self) -> Option<Vec<String>> {
-        if let Some(Token::ArrayString(array_string)) = self.next_token() {
-            return Some(array_string);
+    pub fn expect_comparison(&mut self) -> Result<Token, ParseError> {
+        match self.curr_token() {
+            Some(token) if is_comparison(&token) => {
+                self.advance();
+                Ok(token)
+            }
+            _ => Err(self.error("a comparison")),
         }
-        None
     }
 
-    pub fn curr_token(&self) -> Option<Token> {
-        self.tokens.get(self.position).cloned()
+    pub fn expect_token(&mut self, expected_token: Token) -> Result<Token, ParseError> {
+        match self.curr_token() {
+            Some(token) if token == expected_token => {
+                self.advance();
+                Ok(token)
+            }
+            _ => Err(self.error(&expected_token.to_string())),
+        }
     }
-    pub fn next_token(&mut self) -> Option<Token> {
-        if self.tokens.len() < self.position + 1 {
-            return None;
+
+    pub fn expect_params(&mut self) -> Result<Vec<String>, ParseError> {
+        self.expect_token(Token::LeftParen)?;
+        let mut params = vec![];
+        if let Some(Token::RightParen) = self.curr_token() {
+            self.advance();
+            return Ok(params);
         }
-        let token = self.curr_token();
-        self.advance();
-        token
+        loop {
+            params.push(self.expect_identifier()?);
+            match self.curr_token() {
+                Some(Token::Comma) => self.advance(),
+                Some(Token::RightParen) => {
+                    self.advance();
+                    break;
+                }
+                _ => return Err(self.error("`,` or `)`")),
+            }
+        }
+        Ok(params)
+    }
+
+    pub fn curr_token(&self) -> Option<Token> {
+        self.tokens
+            .get(self.position)
+            .map(|token| token.node.clone())
     }
+    pub fn peek_token(&self) -> Option<Token> {
+        self.tokens
+            .get(self.position + 1)
+            .map(|token| token.node.clone())
+    }
+
     pub fn advance(&mut self) {
         self.position += 1
     }
+}
 
-    pub fn previous(&mut self) {
-        self.position -= 1
-    }
+fn is_statement_keyword(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::Ingredient
+            | Token::Taste
+            | Token::Layer
+            | Token::Simmer
+            | Token::Cook
+            | Token::Burn
+            | Token::Serve
+            | Token::Break
+            | Token::Continue
+            | Token::Plate
+            | Token::Sift
+            | Token::Recipe
+            | Token::Dish
+            | Token::Flavour
+    )
+}
+
+fn is_comparison(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::Hotter
+            | Token::Cooler
+            | Token::Same
+            | Token::HotOrSame
+            | Token::CoolOrSame
+            | Token::NotSame
+    )
 }
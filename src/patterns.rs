@@ -0,0 +1,39 @@
+use crate::{commands::Command, expressions::Expression, spans::Spanned};
+
+pub const TYPE_NAMES: &[&str] = &[
+    "number", "float", "string", "boolean", "array", "pantry", "recipe", "dish", "flavour",
+    "range", "empty",
+];
+
+#[derive(Debug, Clone)]
+pub enum Pattern {
+    Wildcard,
+    Binding(String),
+    Literal(Spanned<Expression>),
+    Range {
+        start: Spanned<Expression>,
+        end: Spanned<Expression>,
+        inclusive: bool,
+    },
+    Array {
+        elements: Vec<Pattern>,
+        rest: Option<String>,
+    },
+    Pantry(Vec<(Spanned<Expression>, Pattern)>),
+    Type {
+        name: String,
+        pattern: Box<Pattern>,
+    },
+    Variant {
+        flavour: Spanned<Expression>,
+        variant: String,
+        payload: Option<Vec<Pattern>>,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct Arm {
+    pub pattern: Pattern,
+    pub guard: Option<Spanned<Expression>>,
+    pub commands: Vec<Spanned<Command>>,
+}
@@ -1,24 +1,208 @@
-use std::collections::HashMap;
+use std::{cell::RefCell, collections::HashMap, fmt, iter, mem, rc::Rc};
 
-use crate::{commands::Command, tokens::Token};
+use crate::{commands::Command, integers::BigInt, spans::Spanned};
+
+pub type Scope = Rc<RefCell<HashMap<String, Value>>>;
 
 pub struct State {
     pub variables: HashMap<String, Value>,
+    pub scopes: Vec<Scope>,
 }
 
 impl State {
     pub fn new() -> Self {
         Self {
             variables: HashMap::new(),
+            scopes: vec![],
         }
     }
 
     pub fn add_variable(&mut self, name: &String, value: Value) -> Option<Value> {
-        self.variables.insert(name.to_string(), value)
+        match self.scopes.last_mut() {
+            Some(scope) => scope.borrow_mut().insert(name.to_string(), value),
+            None => self.variables.insert(name.to_string(), value),
+        }
+    }
+
+    pub fn set_variable(&mut self, name: &String, value: Value) -> Option<Value> {
+        match self
+            .scopes
+            .iter()
+            .rev()
+            .find(|scope| scope.borrow().contains_key(name))
+        {
+            Some(scope) => scope.borrow_mut().insert(name.to_string(), value),
+            None if self.variables.contains_key(name) => {
+                self.variables.insert(name.to_string(), value)
+            }
+            None => None,
+        }
     }
 
     pub fn get_variable(&mut self, name: String) -> Option<Value> {
-        self.variables.get(&name).cloned()
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.borrow().get(&name).cloned())
+            .or_else(|| self.variables.get(&name).cloned())
+    }
+
+    pub fn capture(&self) -> Vec<Scope> {
+        self.scopes.clone()
+    }
+
+    pub fn push_scope(&mut self, scope: HashMap<String, Value>) {
+        self.scopes.push(Rc::new(RefCell::new(scope)));
+    }
+
+    pub fn pop_scope(&mut self) {
+        if let Some(scope) = self.scopes.pop() {
+            release(scope);
+        }
+    }
+
+    pub fn push_frame(
+        &mut self,
+        mut environment: Vec<Scope>,
+        frame: HashMap<String, Value>,
+    ) -> Vec<Scope> {
+        environment.push(Rc::new(RefCell::new(frame)));
+        mem::replace(&mut self.scopes, environment)
+    }
+
+    pub fn pop_frame(&mut self, scopes: Vec<Scope>) -> Option<Scope> {
+        mem::replace(&mut self.scopes, scopes).pop()
+    }
+}
+
+// A recipe captures the scope it is declared in, so a scope that holds its own recipes
+// keeps itself alive through an Rc cycle. When a scope is left and nothing outside it can
+// still reach those recipes, clear it to break the cycle. Recipes that escape their scope
+// keep it alive, and a cycle through an escaped recipe is not reclaimed.
+pub fn release(scope: Scope) {
+    if Rc::strong_count(&scope) == 1 {
+        return;
+    }
+    let unreachable = {
+        let values = scope.borrow();
+        let mut owners = Owners::default();
+        for value in values.values() {
+            owners.collect(value);
+        }
+        let captures = owners
+            .recipes
+            .values()
+            .filter(|(recipe, _)| {
+                recipe
+                    .environment
+                    .iter()
+                    .any(|captured| Rc::ptr_eq(captured, &scope))
+            })
+            .count();
+        owners
+            .recipes
+            .values()
+            .all(|(recipe, count)| Rc::strong_count(recipe) == *count)
+            && owners
+                .dishes
+                .values()
+                .all(|(dish, count)| Rc::strong_count(dish) == *count)
+            && Rc::strong_count(&scope) == captures + 1
+    };
+    if unreachable {
+        let values = mem::take(&mut *scope.borrow_mut());
+        drop(values);
+    }
+}
+
+#[derive(Default)]
+struct Owners<'a> {
+    recipes: HashMap<*const Recipe, (&'a Rc<Recipe>, usize)>,
+    dishes: HashMap<*const Dish, (&'a Rc<Dish>, usize)>,
+}
+
+impl<'a> Owners<'a> {
+    fn collect(&mut self, value: &'a Value) {
+        match value {
+            Value::Array(values) | Value::Variant(_, _, values) => {
+                values.iter().for_each(|value| self.collect(value))
+            }
+            Value::Pantry(entries) => entries.iter().for_each(|(key, value)| {
+                self.collect(key);
+                self.collect(value);
+            }),
+            Value::Function(recipe) => self.collect_recipe(recipe),
+            Value::Dish(dish) => self.collect_dish(dish),
+            Value::Instance(dish, values) => {
+                self.collect_dish(dish);
+                values.iter().for_each(|value| self.collect(value));
+            }
+            _ => {}
+        }
+    }
+
+    fn collect_recipe(&mut self, recipe: &'a Rc<Recipe>) {
+        self.recipes
+            .entry(Rc::as_ptr(recipe))
+            .or_insert((recipe, 0))
+            .1 += 1;
+    }
+
+    fn collect_dish(&mut self, dish: &'a Rc<Dish>) {
+        let owner = self.dishes.entry(Rc::as_ptr(dish)).or_insert((dish, 0));
+        owner.1 += 1;
+        if owner.1 == 1 {
+            dish.methods
+                .iter()
+                .for_each(|(_, method)| self.collect_recipe(method));
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Recipe {
+    pub params: Vec<String>,
+    pub commands: Vec<Spanned<Command>>,
+    pub environment: Vec<Scope>,
+}
+
+#[derive(Debug)]
+pub struct Dish {
+    pub name: String,
+    pub fields: Vec<String>,
+    pub methods: Vec<(String, Rc<Recipe>)>,
+}
+
+#[derive(Debug)]
+pub struct Flavour {
+    pub name: String,
+    pub variants: Vec<(String, Vec<String>)>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Range {
+    pub start: isize,
+    pub end: isize,
+    pub step: isize,
+    pub inclusive: bool,
+}
+
+impl Range {
+    pub fn iter(&self) -> impl Iterator<Item = isize> {
+        let Range {
+            start,
+            end,
+            step,
+            inclusive,
+        } = *self;
+        iter::successors(Some(start), move |current| current.checked_add(step)).take_while(
+            move |current| match (step > 0, inclusive) {
+                (true, true) => *current <= end,
+                (true, false) => *current < end,
+                (false, true) => *current >= end,
+                (false, false) => *current > end,
+            },
+        )
     }
 }
 
@@ -26,8 +210,215 @@ impl State {
 pub enum Value {
     String(String),
     Number(isize),
-    ArrayString(Vec<String>),
-    ArrayNumber(Vec<isize>),
-    Function(Vec<String>, Vec<Command>),
+    BigNumber(BigInt),
+    Float(f64),
+    Array(Vec<Value>),
+    Pantry(Vec<(Value, Value)>),
+    Range(Range),
+    Function(Rc<Recipe>),
+    Dish(Rc<Dish>),
+    Instance(Rc<Dish>, Vec<Value>),
+    Flavour(Rc<Flavour>),
+    Variant(Rc<Flavour>, usize, Vec<Value>),
     Boolean(bool),
+    Empty,
+}
+
+impl Value {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::String(_) => "string",
+            Value::Number(_) | Value::BigNumber(_) => "number",
+            Value::Float(_) => "float",
+            Value::Array(_) => "array",
+            Value::Pantry(_) => "pantry",
+            Value::Range(_) => "range",
+            Value::Function(_) => "recipe",
+            Value::Dish(_) | Value::Instance(..) => "dish",
+            Value::Flavour(_) | Value::Variant(..) => "flavour",
+            Value::Boolean(_) => "boolean",
+            Value::Empty => "empty",
+        }
+    }
+
+    pub fn fmt_nested(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::String(string) => write!(f, "{:?}", string),
+            value => write!(f, "{}", value),
+        }
+    }
+
+    pub fn to_integer(&self) -> Option<BigInt> {
+        match self {
+            Value::Number(number) => Some(BigInt::from(*number)),
+            Value::BigNumber(number) => Some(number.clone()),
+            _ => None,
+        }
+    }
+
+    pub fn to_float(&self) -> Option<f64> {
+        match self {
+            Value::Number(number) => Some(*number as f64),
+            Value::BigNumber(number) => Some(number.to_f64()),
+            Value::Float(float) => Some(*float),
+            _ => None,
+        }
+    }
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::String(left), Value::String(right)) => left == right,
+            (Value::Number(left), Value::Number(right)) => left == right,
+            (Value::BigNumber(left), Value::BigNumber(right)) => left == right,
+            (Value::Float(left), Value::Float(right)) => left == right,
+            (Value::Array(left), Value::Array(right)) => left == right,
+            (Value::Pantry(left), Value::Pantry(right)) => left == right,
+            (Value::Range(left), Value::Range(right)) => left == right,
+            (Value::Function(left), Value::Function(right)) => Rc::ptr_eq(left, right),
+            (Value::Dish(left), Value::Dish(right)) => Rc::ptr_eq(left, right),
+            (Value::Instance(left, left_values), Value::Instance(right, right_values)) => {
+                Rc::ptr_eq(left, right) && left_values == right_values
+            }
+            (Value::Flavour(left), Value::Flavour(right)) => Rc::ptr_eq(left, right),
+            (
+                Value::Variant(left, left_index, left_values),
+                Value::Variant(right, right_index, right_values),
+            ) => {
+                Rc::ptr_eq(left, right) && left_index == right_index && left_values == right_values
+            }
+            (Value::Boolean(left), Value::Boolean(right)) => left == right,
+            (Value::Empty, Value::Empty) => true,
+            _ => false,
+        }
+    }
+}
+
+impl From<BigInt> for Value {
+    fn from(number: BigInt) -> Self {
+        match number.to_isize() {
+            Some(number) => Value::Number(number),
+            None => Value::BigNumber(number),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::String(string) => write!(f, "{}", string),
+            Value::Number(number) => write!(f, "{}", number),
+            Value::BigNumber(number) => write!(f, "{}", number),
+            Value::Float(float) => write!(f, "{:?}", float),
+            Value::Array(array) => {
+                write!(f, "[")?;
+                for (index, value) in array.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    value.fmt_nested(f)?;
+                }
+                write!(f, "]")
+            }
+            Value::Pantry(entries) if entries.is_empty() => write!(f, "pantry {{}}"),
+            Value::Pantry(entries) => {
+                write!(f, "pantry {{")?;
+                for (index, (key, value)) in entries.iter().enumerate() {
+                    write!(f, "{}", if index > 0 { ", " } else { " " })?;
+                    key.fmt_nested(f)?;
+                    write!(f, " is ")?;
+                    value.fmt_nested(f)?;
+                }
+                write!(f, " }}")
+            }
+            Value::Range(range) => {
+                let keyword = if range.inclusive { "to" } else { "until" };
+                write!(
+                    f,
+                    "{} {} {} by {}",
+                    range.start, keyword, range.end, range.step
+                )
+            }
+            Value::Function(recipe) => write!(f, "recipe({})", recipe.params.join(", ")),
+            Value::Dish(dish) => write!(f, "dish {}", dish.name),
+            Value::Instance(dish, _) if dish.fields.is_empty() => write!(f, "{} {{}}", dish.name),
+            Value::Instance(dish, values) => {
+                write!(f, "{} {{", dish.name)?;
+                for (index, (field, value)) in dish.fields.iter().zip(values).enumerate() {
+                    write!(f, "{}{} is ", if index > 0 { ", " } else { " " }, field)?;
+                    value.fmt_nested(f)?;
+                }
+                write!(f, " }}")
+            }
+            Value::Flavour(flavour) => write!(f, "flavour {}", flavour.name),
+            Value::Variant(flavour, index, values) => {
+                write!(f, "{}.{}", flavour.name, flavour.variants[*index].0)?;
+                if values.is_empty() {
+                    return Ok(());
+                }
+                write!(f, "(")?;
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    value.fmt_nested(f)?;
+                }
+                write!(f, ")")
+            }
+            Value::Boolean(boolean) => write!(f, "{}", if *boolean { "open" } else { "close" }),
+            Value::Empty => write!(f, "empty"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn declare(state: &mut State, name: &str) -> Value {
+        let recipe = Recipe {
+            params: vec![],
+            commands: vec![],
+            environment: state.capture(),
+        };
+        let value = Value::Function(Rc::new(recipe));
+        state.add_variable(&name.to_string(), value.clone());
+        value
+    }
+
+    #[test]
+    fn releases_scopes_only_their_own_recipes_capture() {
+        let mut state = State::new();
+        state.push_scope(HashMap::new());
+        let scope = Rc::downgrade(&state.scopes[0]);
+        declare(&mut state, "helper");
+        let method = Recipe {
+            params: vec![],
+            commands: vec![],
+            environment: state.capture(),
+        };
+        let dish = Dish {
+            name: "Box".to_string(),
+            fields: vec![],
+            methods: vec![("open".to_string(), Rc::new(method))],
+        };
+        state.add_variable(&"Box".to_string(), Value::Dish(Rc::new(dish)));
+        state.pop_scope();
+        assert!(scope.upgrade().is_none());
+    }
+
+    #[test]
+    fn keeps_scopes_captured_by_escaped_recipes() {
+        let mut state = State::new();
+        state.push_scope(HashMap::new());
+        let scope = Rc::downgrade(&state.scopes[0]);
+        let escaped = declare(&mut state, "helper");
+        state.pop_scope();
+        assert!(scope.upgrade().is_some());
+        let Value::Function(recipe) = escaped else {
+            unreachable!()
+        };
+        assert!(recipe.environment[0].borrow().contains_key("helper"));
+    }
 }
@@ -1,4 +1,6 @@
-use crate::utils::MathsOperations;
+use std::fmt;
+
+use crate::{integers::BigInt, spans::Spanned, utils::MathsOperations};
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum Token {
@@ -10,21 +12,38 @@ pub enum Token {
     Simmer,
     Cook,
     Burnt,
+    Burn,
     Serve,
+    Break,
+    Continue,
     Plate,
+    Sift,
+    When,
     Recipe,
+    Pantry,
+    Dish,
+    Flavour,
     Fresh,
     Spoiled,
     Mix,
     Separate,
+    Not,
     Number(isize),
+    BigNumber(BigInt),
+    Float(f64),
     Identifier(String),
     StringLiteral(String),
+    Interpolation(Vec<StringPart>),
+    LeftParen,
+    RightParen,
     LeftBracket,
     RightBracket,
+    Dot,
+    DotDot,
+    Ellipsis,
+    Comma,
+    Colon,
     Is,
-    ArrayNumber(Vec<isize>),
-    ArrayString(Vec<String>),
     Hotter,
     Cooler,
     Same,
@@ -32,7 +51,76 @@ pub enum Token {
     HotOrSame,
     CoolOrSame,
     In,
+    To,
+    Until,
+    By,
     Space,
-    Block(Vec<Token>),
+    Block(Vec<Spanned<Token>>),
     Maths(MathsOperations),
 }
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum StringPart {
+    Text(String),
+    Code(Spanned<Vec<Spanned<Token>>>),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::Ingredient => write!(f, "`ingredient`"),
+            Token::Taste => write!(f, "`taste`"),
+            Token::TasteAgain => write!(f, "`taste_again`"),
+            Token::Tasteless => write!(f, "`tasteless`"),
+            Token::Layer => write!(f, "`layer`"),
+            Token::Simmer => write!(f, "`simmer`"),
+            Token::Cook => write!(f, "`cook`"),
+            Token::Burnt => write!(f, "`burnt`"),
+            Token::Burn => write!(f, "`burn`"),
+            Token::Serve => write!(f, "`serve`"),
+            Token::Break => write!(f, "`break`"),
+            Token::Continue => write!(f, "`continue`"),
+            Token::Plate => write!(f, "`plate`"),
+            Token::Sift => write!(f, "`sift`"),
+            Token::When => write!(f, "`when`"),
+            Token::Recipe => write!(f, "`recipe`"),
+            Token::Pantry => write!(f, "`pantry`"),
+            Token::Dish => write!(f, "`dish`"),
+            Token::Flavour => write!(f, "`flavour`"),
+            Token::Fresh => write!(f, "`open`"),
+            Token::Spoiled => write!(f, "`close`"),
+            Token::Mix => write!(f, "`mix`"),
+            Token::Separate => write!(f, "`separate`"),
+            Token::Not => write!(f, "`not`"),
+            Token::Number(number) => write!(f, "number `{}`", number),
+            Token::BigNumber(number) => write!(f, "number `{}`", number),
+            Token::Float(float) => write!(f, "number `{:?}`", float),
+            Token::Identifier(identifier) => write!(f, "identifier `{}`", identifier),
+            Token::StringLiteral(string) => write!(f, "string \"{}\"", string),
+            Token::Interpolation(_) => write!(f, "string"),
+            Token::LeftParen => write!(f, "`(`"),
+            Token::RightParen => write!(f, "`)`"),
+            Token::LeftBracket => write!(f, "`[`"),
+            Token::RightBracket => write!(f, "`]`"),
+            Token::Dot => write!(f, "`.`"),
+            Token::DotDot => write!(f, "`..`"),
+            Token::Ellipsis => write!(f, "`...`"),
+            Token::Comma => write!(f, "`,`"),
+            Token::Colon => write!(f, "`:`"),
+            Token::Is => write!(f, "`is`"),
+            Token::Hotter => write!(f, "`hotter`"),
+            Token::Cooler => write!(f, "`cooler`"),
+            Token::Same => write!(f, "`same`"),
+            Token::NotSame => write!(f, "`notsame`"),
+            Token::HotOrSame => write!(f, "`hotorsame`"),
+            Token::CoolOrSame => write!(f, "`coolorsame`"),
+            Token::In => write!(f, "`in`"),
+            Token::To => write!(f, "`to`"),
+            Token::Until => write!(f, "`until`"),
+            Token::By => write!(f, "`by`"),
+            Token::Space => write!(f, "whitespace"),
+            Token::Block(_) => write!(f, "block"),
+            Token::Maths(operation) => write!(f, "`{}`", operation),
+        }
+    }
+}
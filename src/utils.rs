@@ -1,7 +1,4 @@
-pub enum ValueType {
-    String,
-    Number,
-}
+use std::fmt;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum MathsOperations {
@@ -11,3 +8,15 @@ pub enum MathsOperations {
     Division,
     Mod,
 }
+
+impl fmt::Display for MathsOperations {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MathsOperations::Plus => write!(f, "+"),
+            MathsOperations::Minus => write!(f, "-"),
+            MathsOperations::Multiply => write!(f, "*"),
+            MathsOperations::Division => write!(f, "/"),
+            MathsOperations::Mod => write!(f, "%"),
+        }
+    }
+}